@@ -1,6 +1,6 @@
 use chrono::{Duration, Utc};
 use dotenv::dotenv;
-use fitbit_rs::Client;
+use fitbit_rs::{Client, DateRange};
 
 #[tokio::main]
 async fn main() -> fitbit_rs::Result<()> {
@@ -10,12 +10,12 @@ async fn main() -> fitbit_rs::Result<()> {
         std::env::var("CLIENT_SECRET").expect("CLIENT_SECRET env variable is required");
     let client = Client::new(&client_id, &client_secret)
         .with_cache("tokens.json")
-        .auth_interactive()
-        .await;
+        .auth_interactive(|url| println!("Open the following in your browser: {url}"))
+        .await?;
     let start_date = Utc::now().naive_utc().date() - Duration::days(31);
     let log = client
         .body_time_series()
-        .get_weight_time_series_by_date_range(Some(start_date), None, None)
+        .get_weight_time_series_by_date_range(DateRange::until_today(start_date)?, None)
         .await?;
     dbg!(log);
     Ok(())
@@ -1,2 +1,10 @@
+pub mod activity;
 pub mod body;
 pub mod body_time_series;
+pub mod devices;
+pub mod friends;
+pub mod heart_rate;
+pub mod nutrition;
+pub mod sleep;
+pub mod subscriptions;
+pub mod user;
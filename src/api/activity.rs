@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+
+use crate::{
+    models::activity::{goals, summary},
+    util::date_or_today,
+    Client, Encoding, Result,
+};
+
+pub struct ActivityHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> ActivityHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    /// Get the activity summary for `date`, or today if `None`.
+    pub async fn get_daily_activity_summary(
+        &self,
+        date: Option<NaiveDate>,
+        user_id: Option<&str>,
+    ) -> Result<summary::Response> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+
+        self.client
+            .get(
+                &format!("/1/user/{user_id}/activities/date/{date}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+
+    /// Get the user's daily or weekly activity goals.
+    pub async fn get_activity_goals(
+        &self,
+        period: goals::Period,
+        user_id: Option<&str>,
+    ) -> Result<goals::Goals> {
+        let user_id = user_id.unwrap_or("-");
+        let period = period.as_str();
+
+        let response: goals::Response = self
+            .client
+            .get(
+                &format!("/1/user/{user_id}/activities/goals/{period}.json"),
+                None::<&()>,
+            )
+            .await?;
+        Ok(response.goals)
+    }
+
+    /// Change the daily or weekly activity goals set in `request`, returning
+    /// the updated goals.
+    pub async fn update_activity_goals(
+        &self,
+        period: goals::Period,
+        request: &goals::Request,
+        user_id: Option<&str>,
+    ) -> Result<goals::Goals> {
+        let user_id = user_id.unwrap_or("-");
+        let period = period.as_str();
+
+        let response: goals::Response = self
+            .client
+            .post_encoded(
+                &format!("/1/user/{user_id}/activities/goals/{period}.json"),
+                Some(request),
+                Encoding::Query,
+            )
+            .await?;
+        Ok(response.goals)
+    }
+
+    /// Delete the activity log with `log_id`.
+    pub async fn delete_activity_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1/user/{user_id}/activities/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+}
@@ -1,10 +1,10 @@
-use chrono::{NaiveDate, Utc};
-use serde::Deserialize;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     models::body::{fat::FatLog, weight::WeightLog},
     util::date_or_today,
-    Client, Result,
+    Client, Encoding, Result,
 };
 
 pub struct BodyHandler<'client> {
@@ -62,4 +62,96 @@ impl<'client> BodyHandler<'client> {
             .await?;
         Ok(response.fat)
     }
+
+    /// Log a weight for `date`, at `time` or the end of the day if `None`.
+    /// `weight` is in the client's [`UnitSystem`](crate::units::UnitSystem).
+    pub async fn log_weight(
+        &self,
+        weight: f32,
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+        user_id: Option<&str>,
+    ) -> Result<WeightLog> {
+        let user_id = user_id.unwrap_or("-");
+
+        #[derive(Serialize)]
+        struct Request {
+            weight: f32,
+            date: NaiveDate,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            time: Option<NaiveTime>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Response {
+            pub weight_log: WeightLog,
+        }
+        let response: Response = self
+            .client
+            .post_encoded(
+                &format!("/1/user/{user_id}/body/log/weight.json"),
+                Some(&Request { weight, date, time }),
+                Encoding::Form,
+            )
+            .await?;
+        Ok(response.weight_log)
+    }
+
+    /// Log a body fat percentage for `date`, at `time` or the end of the day
+    /// if `None`.
+    pub async fn log_body_fat(
+        &self,
+        fat: f32,
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+        user_id: Option<&str>,
+    ) -> Result<FatLog> {
+        let user_id = user_id.unwrap_or("-");
+
+        #[derive(Serialize)]
+        struct Request {
+            fat: f32,
+            date: NaiveDate,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            time: Option<NaiveTime>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Response {
+            pub fat_log: FatLog,
+        }
+        let response: Response = self
+            .client
+            .post_encoded(
+                &format!("/1/user/{user_id}/body/log/fat.json"),
+                Some(&Request { fat, date, time }),
+                Encoding::Form,
+            )
+            .await?;
+        Ok(response.fat_log)
+    }
+
+    /// Delete the weight log with `log_id`.
+    pub async fn delete_weight_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1/user/{user_id}/body/log/weight/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+
+    /// Delete the body fat log with `log_id`.
+    pub async fn delete_body_fat_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1/user/{user_id}/body/log/fat/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
 }
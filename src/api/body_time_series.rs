@@ -2,11 +2,34 @@ use chrono::NaiveDate;
 use serde::Deserialize;
 
 use crate::{
+    date_range::{fetch_chunked, DateRange},
     models::body::{fat::FatLog, weight::WeightLog},
     util::date_or_today,
-    Client, Result,
+    Client, Period, Result,
 };
 
+/// Longest range the weight and fat log endpoints accept in one request.
+const MAX_LOG_RANGE_DAYS: u32 = 31;
+
+/// Periods accepted by the weight and fat log endpoints.
+const LOG_PERIODS: &[Period] = &[
+    Period::OneDay,
+    Period::SevenDays,
+    Period::ThirtyDays,
+    Period::OneWeek,
+    Period::OneMonth,
+];
+
+#[derive(Deserialize)]
+struct WeightResponse {
+    pub weight: Vec<WeightLog>,
+}
+
+#[derive(Deserialize)]
+struct FatResponse {
+    pub fat: Vec<FatLog>,
+}
+
 pub struct BodyTimeSeriesHandler<'client> {
     client: &'client Client,
 }
@@ -16,51 +39,90 @@ impl<'client> BodyTimeSeriesHandler<'client> {
         Self { client }
     }
 
-    pub async fn get_weight_time_series_by_date_range(
+    /// Get weight logs for the `period` ending at `date`, or today if `None`.
+    pub async fn get_weight_time_series_by_date(
         &self,
-        start_date: Option<NaiveDate>,
-        end_date: Option<NaiveDate>,
+        date: Option<NaiveDate>,
+        period: Period,
         user_id: Option<&str>,
     ) -> Result<Vec<WeightLog>> {
         let user_id = user_id.unwrap_or("-");
-        let start_date = date_or_today(start_date);
-        let end_date = date_or_today(end_date);
+        let date = date_or_today(date);
+        let period = period.check(LOG_PERIODS)?;
 
-        #[derive(Deserialize)]
-        struct Response {
-            pub weight: Vec<WeightLog>,
-        }
-        let response: Response = self
+        let response: WeightResponse = self
             .client
             .get(
-                &format!("/1/user/{user_id}/body/log/weight/date/{start_date}/{end_date}.json"),
+                &format!("/1/user/{user_id}/body/log/weight/date/{date}/{period}.json"),
                 None::<&()>,
             )
             .await?;
         Ok(response.weight)
     }
 
-    pub async fn get_body_fat_time_series_by_date_range(
+    /// Get weight logs within `range`. Ranges longer than 31 days are
+    /// fetched in multiple requests.
+    pub async fn get_weight_time_series_by_date_range(
+        &self,
+        range: DateRange,
+        user_id: Option<&str>,
+    ) -> Result<Vec<WeightLog>> {
+        let user_id = user_id.unwrap_or("-");
+
+        fetch_chunked(range, MAX_LOG_RANGE_DAYS, |chunk| async move {
+            let response: WeightResponse = self
+                .client
+                .get(
+                    &format!("/1/user/{user_id}/body/log/weight/date/{chunk}.json"),
+                    None::<&()>,
+                )
+                .await?;
+            Ok(response.weight)
+        })
+        .await
+    }
+
+    /// Get body fat logs for the `period` ending at `date`, or today if
+    /// `None`.
+    pub async fn get_body_fat_time_series_by_date(
         &self,
-        start_date: Option<NaiveDate>,
-        end_date: Option<NaiveDate>,
+        date: Option<NaiveDate>,
+        period: Period,
         user_id: Option<&str>,
     ) -> Result<Vec<FatLog>> {
         let user_id = user_id.unwrap_or("-");
-        let start_date = date_or_today(start_date);
-        let end_date = date_or_today(end_date);
+        let date = date_or_today(date);
+        let period = period.check(LOG_PERIODS)?;
 
-        #[derive(Deserialize)]
-        struct Response {
-            pub fat: Vec<FatLog>,
-        }
-        let response: Response = self
+        let response: FatResponse = self
             .client
             .get(
-                &format!("/1/user/{user_id}/body/log/fat/date/{start_date}/{end_date}.json"),
+                &format!("/1/user/{user_id}/body/log/fat/date/{date}/{period}.json"),
                 None::<&()>,
             )
             .await?;
         Ok(response.fat)
     }
+
+    /// Get body fat logs within `range`. Ranges longer than 31 days are
+    /// fetched in multiple requests.
+    pub async fn get_body_fat_time_series_by_date_range(
+        &self,
+        range: DateRange,
+        user_id: Option<&str>,
+    ) -> Result<Vec<FatLog>> {
+        let user_id = user_id.unwrap_or("-");
+
+        fetch_chunked(range, MAX_LOG_RANGE_DAYS, |chunk| async move {
+            let response: FatResponse = self
+                .client
+                .get(
+                    &format!("/1/user/{user_id}/body/log/fat/date/{chunk}.json"),
+                    None::<&()>,
+                )
+                .await?;
+            Ok(response.fat)
+        })
+        .await
+    }
 }
@@ -0,0 +1,24 @@
+use crate::{
+    models::devices::{DeviceInfo, Response},
+    Client, Result,
+};
+
+pub struct DevicesHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> DevicesHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    pub async fn get_devices(&self, user_id: Option<&str>) -> Result<Vec<DeviceInfo>> {
+        let user_id = user_id.unwrap_or("-");
+
+        let response: Response = self
+            .client
+            .get(&format!("/1/user/{user_id}/devices.json"), None::<&()>)
+            .await?;
+        Ok(response.0)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    models::friends::{leaderboard, list},
+    Client, Result,
+};
+
+pub struct FriendsHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> FriendsHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    pub async fn get_friends(&self, user_id: Option<&str>) -> Result<list::Response> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .get(&format!("/1.1/user/{user_id}/friends.json"), None::<&()>)
+            .await
+    }
+
+    pub async fn get_friends_leaderboard(
+        &self,
+        user_id: Option<&str>,
+    ) -> Result<leaderboard::Response> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .get(
+                &format!("/1.1/user/{user_id}/leaderboard/friends.json"),
+                None::<&()>,
+            )
+            .await
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    date_range::{fetch_chunked, DateRange},
+    models::heart_rate::{
+        intraday_time_series::{DetailLevel, Intraday},
+        time_series::{DayEntry, Response},
+    },
+    util::date_or_today,
+    Client, Period, Result,
+};
+
+/// Longest range the heart rate time series endpoint accepts in one request.
+const MAX_RANGE_DAYS: u32 = 365;
+
+/// Periods accepted by the heart rate time series endpoint.
+const PERIODS: &[Period] = &[
+    Period::OneDay,
+    Period::SevenDays,
+    Period::ThirtyDays,
+    Period::OneWeek,
+    Period::OneMonth,
+    Period::OneYear,
+];
+
+pub struct HeartRateHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> HeartRateHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    /// Get daily heart rate summaries for the `period` ending at `date`, or
+    /// today if `None`.
+    pub async fn get_heart_rate_time_series_by_date(
+        &self,
+        date: Option<NaiveDate>,
+        period: Period,
+        user_id: Option<&str>,
+    ) -> Result<Vec<DayEntry>> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+        let period = period.check(PERIODS)?;
+
+        let response: Response = self
+            .client
+            .get(
+                &format!("/1/user/{user_id}/activities/heart/date/{date}/{period}.json"),
+                None::<&()>,
+            )
+            .await?;
+        Ok(response.series)
+    }
+
+    /// Get daily heart rate summaries within `range`. Ranges longer than a
+    /// year are fetched in multiple requests.
+    pub async fn get_heart_rate_time_series_by_date_range(
+        &self,
+        range: DateRange,
+        user_id: Option<&str>,
+    ) -> Result<Vec<DayEntry>> {
+        let user_id = user_id.unwrap_or("-");
+
+        fetch_chunked(range, MAX_RANGE_DAYS, |chunk| async move {
+            let response: Response = self
+                .client
+                .get(
+                    &format!("/1/user/{user_id}/activities/heart/date/{chunk}.json"),
+                    None::<&()>,
+                )
+                .await?;
+            Ok(response.series)
+        })
+        .await
+    }
+
+    /// Get the intraday heart rate data points for `date`, or today if
+    /// `None`. Requires access to intraday data.
+    pub async fn get_heart_rate_intraday_by_date(
+        &self,
+        date: Option<NaiveDate>,
+        detail_level: DetailLevel,
+        user_id: Option<&str>,
+    ) -> Result<Intraday> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+        let detail_level = detail_level.as_str();
+
+        #[derive(Deserialize)]
+        struct IntradayResponse {
+            #[serde(rename = "activities-heart-intraday")]
+            pub intraday: Intraday,
+        }
+        let response: IntradayResponse = self
+            .client
+            .get(
+                &format!("/1/user/{user_id}/activities/heart/date/{date}/1d/{detail_level}.json"),
+                None::<&()>,
+            )
+            .await?;
+        Ok(response.intraday)
+    }
+}
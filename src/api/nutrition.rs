@@ -0,0 +1,75 @@
+use chrono::NaiveDate;
+
+use crate::{
+    models::nutrition::{food_log, water_log},
+    util::date_or_today,
+    Client, Result,
+};
+
+pub struct NutritionHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> NutritionHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    /// Get the food log for `date`, or today if `None`.
+    pub async fn get_food_log(
+        &self,
+        date: Option<NaiveDate>,
+        user_id: Option<&str>,
+    ) -> Result<food_log::Response> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+
+        self.client
+            .get(
+                &format!("/1/user/{user_id}/foods/log/date/{date}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+
+    /// Get the water log for `date`, or today if `None`.
+    pub async fn get_water_log(
+        &self,
+        date: Option<NaiveDate>,
+        user_id: Option<&str>,
+    ) -> Result<water_log::Response> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+
+        self.client
+            .get(
+                &format!("/1/user/{user_id}/foods/log/water/date/{date}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+
+    /// Delete the food log entry with `log_id`.
+    pub async fn delete_food_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1/user/{user_id}/foods/log/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+
+    /// Delete the water log entry with `log_id`.
+    pub async fn delete_water_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1/user/{user_id}/foods/log/water/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+}
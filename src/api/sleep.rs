@@ -0,0 +1,106 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    date_range::{fetch_chunked, DateRange},
+    models::sleep::{
+        goals::{update, Goal},
+        list::Sleep,
+    },
+    util::date_or_today,
+    Client, Encoding, Result,
+};
+
+/// Longest range the sleep log endpoint accepts in one request.
+const MAX_RANGE_DAYS: u32 = 100;
+
+#[derive(Deserialize)]
+struct Response {
+    pub sleep: Vec<Sleep>,
+}
+
+pub struct SleepHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> SleepHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    /// Get sleep logs for `date`, or today if `None`. A sleep belongs to the
+    /// day it ended on.
+    pub async fn get_sleep_log(
+        &self,
+        date: Option<NaiveDate>,
+        user_id: Option<&str>,
+    ) -> Result<Vec<Sleep>> {
+        let user_id = user_id.unwrap_or("-");
+        let date = date_or_today(date);
+
+        let response: Response = self
+            .client
+            .get(
+                &format!("/1.2/user/{user_id}/sleep/date/{date}.json"),
+                None::<&()>,
+            )
+            .await?;
+        Ok(response.sleep)
+    }
+
+    /// Get sleep logs within `range`. Ranges longer than 100 days are
+    /// fetched in multiple requests.
+    pub async fn get_sleep_log_by_date_range(
+        &self,
+        range: DateRange,
+        user_id: Option<&str>,
+    ) -> Result<Vec<Sleep>> {
+        let user_id = user_id.unwrap_or("-");
+
+        let mut sleep = fetch_chunked(range, MAX_RANGE_DAYS, |chunk| async move {
+            let response: Response = self
+                .client
+                .get(
+                    &format!("/1.2/user/{user_id}/sleep/date/{chunk}.json"),
+                    None::<&()>,
+                )
+                .await?;
+            Ok(response.sleep)
+        })
+        .await?;
+        // Each chunk is sorted newest first.
+        sleep.sort_by_key(|s| s.start_time);
+        Ok(sleep)
+    }
+
+    /// Set the user's sleep goal to `min_duration` minutes.
+    pub async fn update_sleep_goal(
+        &self,
+        min_duration: usize,
+        user_id: Option<&str>,
+    ) -> Result<Goal> {
+        let user_id = user_id.unwrap_or("-");
+
+        let response: update::Response = self
+            .client
+            .post_encoded(
+                &format!("/1.2/user/{user_id}/sleep/goal.json"),
+                Some(&update::Request { min_duration }),
+                Encoding::Form,
+            )
+            .await?;
+        Ok(response.goal)
+    }
+
+    /// Delete the sleep log with `log_id`.
+    pub async fn delete_sleep_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()> {
+        let user_id = user_id.unwrap_or("-");
+
+        self.client
+            .delete(
+                &format!("/1.2/user/{user_id}/sleep/{log_id}.json"),
+                None::<&()>,
+            )
+            .await
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    models::subscriptions::{CollectionType, ListResponse, Subscription},
+    Client, Result,
+};
+
+pub struct SubscriptionsHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> SubscriptionsHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    /// Subscribe to changes in `collection`, or every collection if `None`.
+    ///
+    /// `subscriber_id` selects one of the subscriber endpoints configured for
+    /// the application. The default subscriber is used if `None`.
+    pub async fn create_subscription(
+        &self,
+        subscription_id: &str,
+        collection: Option<CollectionType>,
+        subscriber_id: Option<&str>,
+        user_id: Option<&str>,
+    ) -> Result<Subscription> {
+        let route = subscription_route(user_id, collection, Some(subscription_id));
+
+        let mut request = self.client.client.post(self.client.absolute_url(&route)?);
+        if let Some(subscriber_id) = subscriber_id {
+            request = request.header("X-Fitbit-Subscriber-Id", subscriber_id);
+        }
+        let response = self.client.execute(request).await?;
+        self.client.deserialize(&response.text().await?)
+    }
+
+    /// List subscriptions to `collection`, or every collection if `None`.
+    pub async fn get_subscriptions(
+        &self,
+        collection: Option<CollectionType>,
+        user_id: Option<&str>,
+    ) -> Result<Vec<Subscription>> {
+        let route = subscription_route(user_id, collection, None);
+
+        let response: ListResponse = self.client.get(&route, None::<&()>).await?;
+        Ok(response.api_subscriptions)
+    }
+
+    /// Delete a subscription. `collection` must match the one used when
+    /// creating it.
+    pub async fn delete_subscription(
+        &self,
+        subscription_id: &str,
+        collection: Option<CollectionType>,
+        user_id: Option<&str>,
+    ) -> Result<()> {
+        let route = subscription_route(user_id, collection, Some(subscription_id));
+
+        self.client.delete(&route, None::<&()>).await
+    }
+}
+
+fn subscription_route(
+    user_id: Option<&str>,
+    collection: Option<CollectionType>,
+    subscription_id: Option<&str>,
+) -> String {
+    let user_id = user_id.unwrap_or("-");
+    let collection = collection.map_or(String::new(), |c| format!("/{}", c.as_str()));
+
+    match subscription_id {
+        Some(id) => format!("/1/user/{user_id}{collection}/apiSubscriptions/{id}.json"),
+        None => format!("/1/user/{user_id}{collection}/apiSubscriptions.json"),
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{
+    models::user::profile::{update, Response, User},
+    Client, Encoding, Result,
+};
+
+pub struct UserHandler<'client> {
+    client: &'client Client,
+}
+
+impl<'client> UserHandler<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self { client }
+    }
+
+    pub async fn get_profile(&self, user_id: Option<&str>) -> Result<User> {
+        let user_id = user_id.unwrap_or("-");
+
+        let response: Response = self
+            .client
+            .get(&format!("/1/user/{user_id}/profile.json"), None::<&()>)
+            .await?;
+        Ok(response.user)
+    }
+
+    /// Change the profile fields set in `request`, returning the updated
+    /// profile.
+    pub async fn update_profile(
+        &self,
+        request: &update::Request,
+        user_id: Option<&str>,
+    ) -> Result<User> {
+        let user_id = user_id.unwrap_or("-");
+
+        let response: Response = self
+            .client
+            .post_encoded(
+                &format!("/1/user/{user_id}/profile.json"),
+                Some(request),
+                Encoding::Form,
+            )
+            .await?;
+        Ok(response.user)
+    }
+}
@@ -0,0 +1,368 @@
+//! Query and export Fitbit data from the command line.
+//!
+//! Reads the application credentials from the `CLIENT_ID` and
+//! `CLIENT_SECRET` environment variables. Run `fitbit login` once to
+//! authorize and cache a token; every other command reuses it.
+
+use std::{io::Write, path::PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use fitbit_rs::{
+    export::{self, Exporter},
+    models::{
+        devices::{BatteryLevel, Device, DeviceType},
+        heart_rate::intraday_time_series::DetailLevel,
+    },
+    weight_import::{self, WeightImporter},
+    Client, DateRange,
+};
+use serde::Serialize;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "fitbit", version, about = "Query and export Fitbit data")]
+struct Cli {
+    #[arg(long, env = "CLIENT_ID", hide_env_values = true)]
+    client_id: String,
+    #[arg(long, env = "CLIENT_SECRET", hide_env_values = true)]
+    client_secret: String,
+    /// Where the OAuth token is cached.
+    #[arg(long, env = "FITBIT_TOKENS", default_value = "tokens.json")]
+    tokens: PathBuf,
+    /// How query results are printed.
+    #[arg(long, short, value_enum, default_value_t = Output::Table)]
+    output: Output,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Authorize in the browser and cache the token.
+    Login,
+    /// Show the profile of the logged in user.
+    Whoami,
+    /// List weight logs.
+    Weight(Range),
+    /// List sleep logs.
+    Sleep(Range),
+    /// List daily resting heart rate, or intraday heart rate for a day.
+    Hr {
+        #[command(flatten)]
+        range: Range,
+        /// Show the data points of the `--to` day instead.
+        #[arg(long)]
+        intraday: bool,
+    },
+    /// List paired devices.
+    Devices,
+    /// Export all data to a directory.
+    Export {
+        #[command(flatten)]
+        range: Range,
+        /// Output directory. Re-running an interrupted export resumes it.
+        #[arg(long, default_value = "fitbit-export")]
+        dir: PathBuf,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+    },
+    /// Log weight and body fat from a CSV file, skipping measurements which
+    /// are already logged.
+    ImportWeight {
+        /// CSV file with the columns date, time, weight, fat and unit.
+        file: PathBuf,
+        /// Minutes a measurement and an existing log may be apart to count
+        /// as the same, at most a day.
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(..=1440))]
+        tolerance: u32,
+        /// Show the logs which would be created without creating them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(clap::Args)]
+struct Range {
+    /// First day, defaults to 30 days before `--to`.
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// Last day, defaults to today.
+    #[arg(long)]
+    to: Option<NaiveDate>,
+}
+
+impl Range {
+    fn end(&self) -> NaiveDate {
+        self.to.unwrap_or_else(|| Utc::now().naive_utc().date())
+    }
+
+    fn date_range(&self) -> fitbit_rs::Result<DateRange> {
+        let end = self.end();
+        match self.from {
+            Some(start) => DateRange::new(start, end),
+            None => Ok(DateRange::ending_at(end, 31)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = Client::new(&cli.client_id, &cli.client_secret).with_cache(&cli.tokens);
+    let client = match cli.command {
+        Command::Login => {
+            client
+                .auth_interactive(|url| println!("Open the following in your browser: {url}"))
+                .await?;
+            println!("Token cached in {}", cli.tokens.display());
+            return Ok(());
+        }
+        _ => client
+            .auth_from_cache()
+            .map_err(|e| format!("{e}, run `fitbit login` first"))?,
+    };
+
+    match cli.command {
+        Command::Login => unreachable!(),
+        Command::Whoami => {
+            let user = client.user().get_profile(None).await?;
+            let row = ProfileRow {
+                id: user.encoded_id,
+                name: user.display_name,
+                member_since: user.member_since,
+                timezone: user.timezone,
+            };
+            print_rows(cli.output, &[row])
+        }
+        Command::Weight(range) => {
+            let logs = client
+                .body_time_series()
+                .get_weight_time_series_by_date_range(range.date_range()?, None)
+                .await?;
+            let rows: Vec<_> = logs
+                .into_iter()
+                .map(|l| WeightRow {
+                    date: l.date,
+                    time: l.time,
+                    weight: l.weight,
+                    bmi: l.bmi,
+                })
+                .collect();
+            print_rows(cli.output, &rows)
+        }
+        Command::Sleep(range) => {
+            let sleep = client
+                .sleep()
+                .get_sleep_log_by_date_range(range.date_range()?, None)
+                .await?;
+            let rows: Vec<_> = sleep
+                .into_iter()
+                .map(|s| SleepRow {
+                    date: s.date_of_sleep,
+                    start: s.start_time,
+                    end: s.end_time,
+                    minutes_asleep: s.minutes_asleep,
+                    efficiency: s.efficiency,
+                })
+                .collect();
+            print_rows(cli.output, &rows)
+        }
+        Command::Hr {
+            range,
+            intraday: true,
+        } => {
+            let intraday = client
+                .heart_rate()
+                .get_heart_rate_intraday_by_date(Some(range.end()), DetailLevel::OneMinute, None)
+                .await?;
+            let rows: Vec<_> = intraday
+                .dataset
+                .into_iter()
+                .map(|e| IntradayRow {
+                    time: e.time,
+                    bpm: e.value,
+                })
+                .collect();
+            print_rows(cli.output, &rows)
+        }
+        Command::Hr {
+            range,
+            intraday: false,
+        } => {
+            let days = client
+                .heart_rate()
+                .get_heart_rate_time_series_by_date_range(range.date_range()?, None)
+                .await?;
+            let rows: Vec<_> = days
+                .into_iter()
+                .map(|d| HeartRateRow {
+                    date: d.date_time,
+                    resting_heart_rate: d.value.resting_heart_rate,
+                })
+                .collect();
+            print_rows(cli.output, &rows)
+        }
+        Command::Devices => {
+            let devices = client.devices().get_devices(None).await?;
+            let rows: Vec<_> = devices
+                .into_iter()
+                .map(|d| DeviceRow {
+                    id: d.id,
+                    device: d.device_version,
+                    device_type: d.ty,
+                    battery: d.battery,
+                    battery_level: d.battery_percentage,
+                    last_sync: d.last_sync_time,
+                })
+                .collect();
+            print_rows(cli.output, &rows)
+        }
+        Command::Export { range, dir, format } => {
+            let format = match format {
+                ExportFormat::Jsonl => export::Format::JsonLines,
+                ExportFormat::Csv => export::Format::Csv,
+            };
+            Exporter::new(&client, &dir, range.date_range()?)
+                .with_format(format)
+                .run()
+                .await?;
+            println!("Exported to {}", dir.display());
+            Ok(())
+        }
+        Command::ImportWeight {
+            file,
+            tolerance,
+            dry_run,
+        } => {
+            let measurements = weight_import::read_csv(std::fs::File::open(&file)?)?;
+            let report = WeightImporter::new(&client)
+                .with_tolerance(TimeDelta::minutes(i64::from(tolerance)))
+                .with_dry_run(dry_run)
+                .run(&measurements)
+                .await?;
+            print!("{report}");
+            if !report.failed.is_empty() {
+                return Err("some logs could not be created".into());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_rows<T: Serialize>(output: Output, rows: &[T]) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    match output {
+        Output::Json => {
+            serde_json::to_writer_pretty(&mut stdout, rows)?;
+            writeln!(stdout)?;
+        }
+        Output::Csv => {
+            let mut writer = csv::Writer::from_writer(stdout);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        Output::Table => {
+            // Go through CSV to get the cells in field order.
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            let data = writer.into_inner().map_err(|e| e.into_error())?;
+            let records = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(data.as_slice())
+                .into_records()
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let mut widths = Vec::new();
+            for record in &records {
+                widths.resize(widths.len().max(record.len()), 0);
+                for (width, cell) in widths.iter_mut().zip(record) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            for record in &records {
+                let line: Vec<_> = record
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect();
+                writeln!(stdout, "{}", line.join("  ").trim_end())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ProfileRow {
+    id: String,
+    name: String,
+    member_since: NaiveDate,
+    timezone: String,
+}
+
+#[derive(Serialize)]
+struct WeightRow {
+    date: NaiveDate,
+    time: NaiveTime,
+    weight: f32,
+    bmi: f32,
+}
+
+#[derive(Serialize)]
+struct SleepRow {
+    date: NaiveDate,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    minutes_asleep: u64,
+    efficiency: u64,
+}
+
+#[derive(Serialize)]
+struct HeartRateRow {
+    date: NaiveDate,
+    resting_heart_rate: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct IntradayRow {
+    time: NaiveTime,
+    bpm: usize,
+}
+
+#[derive(Serialize)]
+struct DeviceRow {
+    id: String,
+    device: Device,
+    #[serde(rename = "type")]
+    device_type: DeviceType,
+    battery: BatteryLevel,
+    battery_level: usize,
+    last_sync: NaiveDateTime,
+}
@@ -0,0 +1,220 @@
+//! A synchronous client, for code which isn't async.
+//!
+//! [`Client`] wraps an async [`crate::Client`] and drives it on an internal
+//! single-threaded runtime. Its handlers mirror the async ones method for
+//! method and return the same model types.
+//!
+//! ```no_run
+//! # fn run() -> fitbit_rs::Result<()> {
+//! let client = fitbit_rs::Client::new("client id", "client secret")
+//!     .with_cache("tokens.json")
+//!     .auth_from_cache()?;
+//! let client = fitbit_rs::blocking::Client::new(client)?;
+//! let weight = client.body().get_weight_log(None, None)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use chrono::{NaiveDate, NaiveTime};
+use url::Url;
+
+use crate::{
+    models::{
+        activity::{goals, summary},
+        body::{fat::FatLog, weight::WeightLog},
+        devices::DeviceInfo,
+        friends::{leaderboard, list},
+        heart_rate::{
+            intraday_time_series::{DetailLevel, Intraday},
+            time_series::DayEntry,
+        },
+        nutrition::{food_log, water_log},
+        sleep::{goals::Goal, list::Sleep},
+        subscriptions::{CollectionType, Subscription},
+        user::profile::{update, User},
+    },
+    DateRange, Period, RateLimit, Result,
+};
+
+/// A blocking Fitbit client.
+pub struct Client {
+    inner: crate::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    /// Wrap a configured async client.
+    pub fn new(inner: crate::Client) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// See [`crate::Client::auth_interactive`].
+    pub fn auth_interactive(self, open_url: impl FnOnce(&Url)) -> Result<Self> {
+        let Self { inner, runtime } = self;
+        let inner = runtime.block_on(inner.auth_interactive(open_url))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The wrapped async client.
+    pub fn inner(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Run any future to completion, e.g. a raw request made with
+    /// [`Client::inner`].
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Rate limit state reported by the most recent response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// Generate a blocking handler for each async handler, forwarding every
+/// method to it.
+///
+/// Signatures are written out again, but forwarding the arguments to the
+/// async method makes the compiler check them. Methods missing from the
+/// list are caught by the `mirrors_async_handlers` test.
+macro_rules! handlers {
+    ($(
+        $accessor:ident => $handler:ident {
+            $(fn $method:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*
+        }
+    )*) => {
+        impl Client {
+            $(
+                #[doc = concat!("Blocking version of [`crate::Client::", stringify!($accessor), "`].")]
+                pub fn $accessor(&self) -> $handler<'_> {
+                    $handler { client: self }
+                }
+            )*
+        }
+
+        $(
+            #[doc = concat!("Returned by [`Client::", stringify!($accessor), "`].")]
+            pub struct $handler<'client> {
+                client: &'client Client,
+            }
+
+            impl $handler<'_> {
+                $(
+                    #[doc = concat!("See the async [`", stringify!($accessor), "`](crate::Client::", stringify!($accessor), ") handler.")]
+                    pub fn $method(&self $(, $arg: $ty)*) -> $ret {
+                        self.client
+                            .block_on(self.client.inner.$accessor().$method($($arg),*))
+                    }
+                )*
+            }
+        )*
+    };
+}
+
+handlers! {
+    activity => ActivityHandler {
+        fn get_daily_activity_summary(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<summary::Response>;
+        fn get_activity_goals(&self, period: goals::Period, user_id: Option<&str>) -> Result<goals::Goals>;
+        fn update_activity_goals(&self, period: goals::Period, request: &goals::Request, user_id: Option<&str>) -> Result<goals::Goals>;
+        fn delete_activity_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+    }
+    body => BodyHandler {
+        fn get_weight_log(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<Vec<WeightLog>>;
+        fn get_body_fat_log(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<Vec<FatLog>>;
+        fn log_weight(&self, weight: f32, date: NaiveDate, time: Option<NaiveTime>, user_id: Option<&str>) -> Result<WeightLog>;
+        fn log_body_fat(&self, fat: f32, date: NaiveDate, time: Option<NaiveTime>, user_id: Option<&str>) -> Result<FatLog>;
+        fn delete_weight_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+        fn delete_body_fat_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+    }
+    body_time_series => BodyTimeSeriesHandler {
+        fn get_weight_time_series_by_date(&self, date: Option<NaiveDate>, period: Period, user_id: Option<&str>) -> Result<Vec<WeightLog>>;
+        fn get_weight_time_series_by_date_range(&self, range: DateRange, user_id: Option<&str>) -> Result<Vec<WeightLog>>;
+        fn get_body_fat_time_series_by_date(&self, date: Option<NaiveDate>, period: Period, user_id: Option<&str>) -> Result<Vec<FatLog>>;
+        fn get_body_fat_time_series_by_date_range(&self, range: DateRange, user_id: Option<&str>) -> Result<Vec<FatLog>>;
+    }
+    devices => DevicesHandler {
+        fn get_devices(&self, user_id: Option<&str>) -> Result<Vec<DeviceInfo>>;
+    }
+    friends => FriendsHandler {
+        fn get_friends(&self, user_id: Option<&str>) -> Result<list::Response>;
+        fn get_friends_leaderboard(&self, user_id: Option<&str>) -> Result<leaderboard::Response>;
+    }
+    heart_rate => HeartRateHandler {
+        fn get_heart_rate_time_series_by_date(&self, date: Option<NaiveDate>, period: Period, user_id: Option<&str>) -> Result<Vec<DayEntry>>;
+        fn get_heart_rate_time_series_by_date_range(&self, range: DateRange, user_id: Option<&str>) -> Result<Vec<DayEntry>>;
+        fn get_heart_rate_intraday_by_date(&self, date: Option<NaiveDate>, detail_level: DetailLevel, user_id: Option<&str>) -> Result<Intraday>;
+    }
+    nutrition => NutritionHandler {
+        fn get_food_log(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<food_log::Response>;
+        fn get_water_log(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<water_log::Response>;
+        fn delete_food_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+        fn delete_water_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+    }
+    sleep => SleepHandler {
+        fn get_sleep_log(&self, date: Option<NaiveDate>, user_id: Option<&str>) -> Result<Vec<Sleep>>;
+        fn get_sleep_log_by_date_range(&self, range: DateRange, user_id: Option<&str>) -> Result<Vec<Sleep>>;
+        fn update_sleep_goal(&self, min_duration: usize, user_id: Option<&str>) -> Result<Goal>;
+        fn delete_sleep_log(&self, log_id: u64, user_id: Option<&str>) -> Result<()>;
+    }
+    subscriptions => SubscriptionsHandler {
+        fn create_subscription(&self, subscription_id: &str, collection: Option<CollectionType>, subscriber_id: Option<&str>, user_id: Option<&str>) -> Result<Subscription>;
+        fn get_subscriptions(&self, collection: Option<CollectionType>, user_id: Option<&str>) -> Result<Vec<Subscription>>;
+        fn delete_subscription(&self, subscription_id: &str, collection: Option<CollectionType>, user_id: Option<&str>) -> Result<()>;
+    }
+    user => UserHandler {
+        fn get_profile(&self, user_id: Option<&str>) -> Result<User>;
+        fn update_profile(&self, request: &update::Request, user_id: Option<&str>) -> Result<User>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_on() {
+        let client = Client::new(crate::Client::new("id", "secret")).unwrap();
+        assert_eq!(client.block_on(async { 1 + 1 }), 2);
+        assert!(client.rate_limit().is_none());
+    }
+
+    #[test]
+    fn mirrors_async_handlers() {
+        let blocking = include_str!("blocking.rs");
+        let handlers = [
+            ("activity", include_str!("api/activity.rs")),
+            ("body", include_str!("api/body.rs")),
+            ("body_time_series", include_str!("api/body_time_series.rs")),
+            ("devices", include_str!("api/devices.rs")),
+            ("friends", include_str!("api/friends.rs")),
+            ("heart_rate", include_str!("api/heart_rate.rs")),
+            ("nutrition", include_str!("api/nutrition.rs")),
+            ("sleep", include_str!("api/sleep.rs")),
+            ("subscriptions", include_str!("api/subscriptions.rs")),
+            ("user", include_str!("api/user.rs")),
+        ];
+        for (accessor, source) in handlers {
+            let methods = blocking
+                .split(&format!("\n    {accessor} => "))
+                .nth(1)
+                .and_then(|rest| rest.split('}').next())
+                .unwrap_or_else(|| panic!("no blocking {accessor} handler"));
+            for line in source.lines() {
+                let Some(method) = line.trim().strip_prefix("pub async fn ") else {
+                    continue;
+                };
+                let name = method.split(['(', '<']).next().unwrap();
+                assert!(
+                    methods.contains(&format!("fn {name}(")),
+                    "blocking {accessor} handler is missing {name}"
+                );
+            }
+        }
+    }
+}
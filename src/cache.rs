@@ -0,0 +1,380 @@
+//! Cache responses for days which are unlikely to change.
+//!
+//! Data for a day keeps changing while devices sync, but days long past are
+//! practically immutable. A [`CachePolicy`] picks how long to keep a `GET`
+//! response based on the newest date in its route; routes without a date,
+//! like the profile or devices, and anything about today are never cached.
+//! Today is taken in UTC, which may still be yesterday for the user, so
+//! yesterday isn't cached either.
+//!
+//! Entries are keyed by the request URL and the unit system, with the `-`
+//! standing for the authorized user replaced by their ID from the token. A
+//! cache can thus be shared between the clients of a
+//! [`ClientPool`](crate::ClientPool), or persisted between runs. Requests for
+//! `-` aren't cached if the token doesn't say who the user is.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, Utc};
+
+/// Storage for cached response bodies.
+pub trait ResponseCache {
+    /// The body stored for `key`, unless it expired.
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+    /// Store `body` for `key` for up to `ttl`.
+    fn put(&self, key: &str, body: Vec<u8>, ttl: Duration);
+}
+
+impl<T: ResponseCache> ResponseCache for Arc<T> {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
+
+    fn put(&self, key: &str, body: Vec<u8>, ttl: Duration) {
+        (**self).put(key, body, ttl)
+    }
+}
+
+/// How long to cache responses, by the age of the requested day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    recent_days: u32,
+    recent_ttl: Option<Duration>,
+    historical_ttl: Option<Duration>,
+}
+
+impl Default for CachePolicy {
+    /// Days from two days up to a week ago are cached for an hour, older
+    /// days for 30 days.
+    fn default() -> Self {
+        Self {
+            recent_days: 7,
+            recent_ttl: Some(Duration::from_secs(60 * 60)),
+            historical_ttl: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl CachePolicy {
+    /// Days before today which count as recent.
+    pub fn with_recent_days(mut self, recent_days: u32) -> Self {
+        self.recent_days = recent_days;
+        self
+    }
+
+    /// How long to cache recent days, or `None` to not cache them.
+    pub fn with_recent_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.recent_ttl = ttl;
+        self
+    }
+
+    /// How long to cache days older than the recent ones, or `None` to not
+    /// cache them.
+    pub fn with_historical_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.historical_ttl = ttl;
+        self
+    }
+
+    /// How long to cache the response to `path`, if at all.
+    pub(crate) fn ttl(&self, path: &str) -> Option<Duration> {
+        self.ttl_on(path, Utc::now().date_naive())
+    }
+
+    fn ttl_on(&self, path: &str, today: NaiveDate) -> Option<Duration> {
+        let newest = path
+            .split('/')
+            .map(|segment| segment.trim_end_matches(".json"))
+            .filter_map(|segment| NaiveDate::parse_from_str(segment, "%Y-%m-%d").ok())
+            .max()?;
+        match (today - newest).num_days() {
+            // West of UTC, yesterday may still be the user's today.
+            ..=1 => None,
+            age if age <= i64::from(self.recent_days) => self.recent_ttl,
+            _ => self.historical_ttl,
+        }
+    }
+}
+
+/// An in-memory cache, evicting the least recently used entry once full.
+#[derive(Debug)]
+pub struct LruCache {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<String, Entry>,
+    /// Keys by when they were last used.
+    order: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    body: Vec<u8>,
+    expires_at: Instant,
+    used_at: u64,
+}
+
+impl LruCache {
+    /// A cache holding up to `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        // A panic while holding the lock can't leave the maps inconsistent
+        // enough to matter for a cache.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Lru {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.used_at);
+        Some(entry)
+    }
+}
+
+impl ResponseCache for LruCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut lru = self.lock();
+        let now = lru.tick();
+        let entry = lru.entries.get_mut(key)?;
+        if entry.expires_at <= Instant::now() {
+            lru.remove(key);
+            return None;
+        }
+        let used_at = std::mem::replace(&mut entry.used_at, now);
+        let body = entry.body.clone();
+        lru.order.remove(&used_at);
+        lru.order.insert(now, key.to_owned());
+        Some(body)
+    }
+
+    fn put(&self, key: &str, body: Vec<u8>, ttl: Duration) {
+        let mut lru = self.lock();
+        lru.remove(key);
+        while lru.entries.len() >= self.capacity {
+            let Some((_, oldest)) = lru.order.pop_first() else {
+                break;
+            };
+            lru.entries.remove(&oldest);
+        }
+        let used_at = lru.tick();
+        lru.order.insert(used_at, key.to_owned());
+        lru.entries.insert(
+            key.to_owned(),
+            Entry {
+                body,
+                expires_at: Instant::now() + ttl,
+                used_at,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use futures_util::FutureExt;
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::{
+        middleware::{LocalBoxFuture, Middleware, Next},
+        Client, Error, RetryPolicy,
+    };
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ttl_by_age() {
+        let policy = CachePolicy::default();
+        let today = date("2022-03-31");
+        let ttl = |path| policy.ttl_on(path, today);
+
+        assert_eq!(ttl("/1/user/-/profile.json"), None);
+        assert_eq!(ttl("/1/user/-/body/log/weight/date/2022-03-31.json"), None);
+        assert_eq!(ttl("/1/user/-/body/log/weight/date/2022-03-30.json"), None);
+        assert_eq!(
+            ttl("/1/user/-/body/log/weight/date/2022-03-29.json"),
+            Some(Duration::from_secs(60 * 60))
+        );
+        assert_eq!(
+            ttl("/1/user/-/body/log/weight/date/2022-03-28.json"),
+            Some(Duration::from_secs(60 * 60))
+        );
+        assert_eq!(
+            ttl("/1/user/-/body/log/weight/date/2022-01-01/2022-01-31.json"),
+            Some(Duration::from_secs(30 * 24 * 60 * 60))
+        );
+        // The newest day decides.
+        assert_eq!(
+            ttl("/1/user/-/body/log/weight/date/2022-01-01/2022-03-31.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = LruCache::new(2);
+        let ttl = Duration::from_secs(60);
+        cache.put("a", b"1".to_vec(), ttl);
+        cache.put("b", b"2".to_vec(), ttl);
+        assert_eq!(cache.get("a"), Some(b"1".to_vec()));
+        cache.put("c", b"3".to_vec(), ttl);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(b"1".to_vec()));
+        assert_eq!(cache.get("c"), Some(b"3".to_vec()));
+    }
+
+    #[test]
+    fn expires() {
+        let cache = LruCache::new(2);
+        cache.put("a", b"1".to_vec(), Duration::ZERO);
+        assert_eq!(cache.get("a"), None);
+        assert!(cache.is_empty());
+    }
+
+    /// Answers with `status` and how many requests it got so far.
+    struct Counter {
+        status: u16,
+        count: Arc<AtomicU32>,
+    }
+
+    impl Middleware for Counter {
+        fn handle<'a>(
+            &'a self,
+            _request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, crate::Result<reqwest::Response>> {
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            let response = http::Response::builder()
+                .status(self.status)
+                .body(format!(r#"{{"count":{count}}}"#))
+                .unwrap();
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    /// A client authorized as `user_id`, if any, answered by a [`Counter`].
+    fn counting_client(
+        status: u16,
+        user_id: Option<&str>,
+        cache: &Arc<LruCache>,
+    ) -> (Client, Arc<AtomicU32>) {
+        let count = Arc::default();
+        let client = Client::new("id", "secret")
+            .with_retry_policy(RetryPolicy::default().with_max_attempts(1))
+            .with_response_cache(cache.clone())
+            .with_middleware(Counter {
+                status,
+                count: Arc::clone(&count),
+            });
+        if let Some(user_id) = user_id {
+            client.auth.set_test_token(user_id);
+        }
+        (client, count)
+    }
+
+    const ROUTE: &str = "/1/user/-/body/log/weight/date/2020-01-01.json";
+
+    #[tokio::test]
+    async fn client_caches_get() {
+        let cache = Arc::new(LruCache::new(8));
+        let (client, count) = counting_client(200, Some("A"), &cache);
+        for _ in 0..2 {
+            let value: serde_json::Value = client.get(ROUTE, None::<&()>).await.unwrap();
+            assert_eq!(value["count"], 1);
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // Entries are shared by the clients of the same user, whether they
+        // use `-` or the user's ID.
+        let (same, count) = counting_client(200, Some("A"), &cache);
+        let _: serde_json::Value = same.get(ROUTE, None::<&()>).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        let (anyone, count) = counting_client(200, None, &cache);
+        let _: serde_json::Value = anyone
+            .get(&ROUTE.replace("/-/", "/A/"), None::<&()>)
+            .await
+            .unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        let (other, count) = counting_client(200, Some("B"), &cache);
+        let _: serde_json::Value = other.get(ROUTE, None::<&()>).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn client_skips_unknown_user() {
+        let cache = Arc::new(LruCache::new(8));
+        let (client, count) = counting_client(200, None, &cache);
+        for _ in 0..2 {
+            let _: serde_json::Value = client.get(ROUTE, None::<&()>).await.unwrap();
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_skips_other_methods() {
+        let cache = Arc::new(LruCache::new(8));
+        let (client, count) = counting_client(200, Some("A"), &cache);
+        for _ in 0..2 {
+            let _: serde_json::Value = client.post(ROUTE, None::<&()>).await.unwrap();
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_skips_errors() {
+        let cache = Arc::new(LruCache::new(8));
+        let (client, count) = counting_client(404, Some("A"), &cache);
+        for _ in 0..2 {
+            let err = client
+                .get::<serde_json::Value, _>(ROUTE, None::<&()>)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Api {
+                    status: StatusCode::NOT_FOUND,
+                    ..
+                }
+            ));
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,480 @@
+//! Record responses to a cassette file and replay them without a network.
+//!
+//! A [`Recorder`] is a [`Middleware`] which saves every request it sees
+//! along with its response. A [`Replayer`] answers requests from such a
+//! cassette instead of sending them, which lets tests run handlers against
+//! real Fitbit payloads:
+//!
+//! ```no_run
+//! # fn run() -> fitbit_rs::Result<()> {
+//! use fitbit_rs::cassette::{Recorder, Replayer};
+//!
+//! // Once, against the real API.
+//! let client = fitbit_rs::Client::new("client id", "client secret")
+//!     .with_middleware(Recorder::new("cassettes/devices.json")?);
+//!
+//! // In tests.
+//! let client = fitbit_rs::Client::new("client id", "client secret")
+//!     .with_middleware(Replayer::open("cassettes/devices.json")?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Cassettes never contain request headers, so tokens aren't recorded. User
+//! IDs in routes are replaced by `-`, and values of personal fields in
+//! response bodies are replaced according to [`Redactions`].
+
+use std::{collections::BTreeSet, path::PathBuf, sync::Mutex};
+
+use futures_util::FutureExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    middleware::{LocalBoxFuture, Middleware, Next},
+    Error, Result,
+};
+
+/// Response headers worth keeping.
+const RECORDED_HEADERS: [&str; 4] = [
+    "content-type",
+    "fitbit-rate-limit-limit",
+    "fitbit-rate-limit-remaining",
+    "fitbit-rate-limit-reset",
+];
+
+/// Recorded requests and responses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query, with user IDs redacted.
+    pub route: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    fn new(request: &reqwest::Request) -> Self {
+        let url = request.url();
+        let mut route = redact_route(url.path());
+        if let Some(query) = url.query() {
+            route = format!("{route}?{query}");
+        }
+        Self {
+            method: request.method().to_string(),
+            route,
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn into_response(self) -> Result<reqwest::Response> {
+        let mut response = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+        let response = response
+            .body(self.body)
+            .map_err(|e| Error::Cassette(e.to_string()))?;
+        Ok(response.into())
+    }
+}
+
+/// Replace the user ID in `path` with `-`.
+fn redact_route(path: &str) -> String {
+    let mut previous = "";
+    path.split('/')
+        .map(|segment| {
+            let redacted = if previous == "user" { "-" } else { segment };
+            previous = segment;
+            redacted
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Which response fields hold personal data.
+///
+/// String values of these fields are replaced: dates by `1970-01-01`, URLs
+/// by `https://example.com/redacted` and anything else by `REDACTED`, so
+/// redacted responses still parse.
+///
+/// A field is given by its camelCase JSON name, optionally preceded by the
+/// names of the objects it is nested in, e.g. `user.fullName` only matches
+/// `fullName` inside a `user` object. Arrays don't count as a level. Plain
+/// names such as `name` match everywhere, including heart rate zones, foods
+/// and badges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redactions {
+    fields: BTreeSet<String>,
+}
+
+impl Default for Redactions {
+    /// The profile, friends' and leaderboard users' profiles and IDs,
+    /// subscription owners and device MAC addresses.
+    fn default() -> Self {
+        Self::none().with_fields([
+            "user.aboutMe",
+            "user.avatar",
+            "user.avatar150",
+            "user.avatar640",
+            "user.city",
+            "user.country",
+            "user.dateOfBirth",
+            "user.displayName",
+            "user.email",
+            "user.encodedId",
+            "user.firstName",
+            "user.fullName",
+            "user.lastName",
+            "user.state",
+            "attributes.avatar",
+            "attributes.name",
+            "data.id",
+            "included.id",
+            "ownerId",
+            "subscriberId",
+            "mac",
+        ])
+    }
+}
+
+impl Redactions {
+    /// Redact nothing.
+    pub fn none() -> Self {
+        Self {
+            fields: BTreeSet::new(),
+        }
+    }
+
+    /// Also redact `fields`, see [`Redactions`] for how they are given.
+    pub fn with_fields<'a>(mut self, fields: impl IntoIterator<Item = &'a str>) -> Self {
+        self.fields.extend(fields.into_iter().map(str::to_owned));
+        self
+    }
+
+    /// Redact a response body. Bodies which aren't JSON are kept as is.
+    fn apply(&self, body: String) -> String {
+        match serde_json::from_str(&body) {
+            Ok(mut value) => {
+                self.redact(&mut value, &mut Vec::new());
+                value.to_string()
+            }
+            Err(_) => body,
+        }
+    }
+
+    /// Redact `value`, found at `path`.
+    fn redact(&self, value: &mut serde_json::Value, path: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object {
+                    path.push(key.clone());
+                    match value {
+                        serde_json::Value::String(s) if self.matches(path) => {
+                            *s = placeholder(s).to_owned();
+                        }
+                        value => self.redact(value, path),
+                    }
+                    path.pop();
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|v| self.redact(v, path));
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `path` ends with one of the fields.
+    fn matches(&self, path: &[String]) -> bool {
+        self.fields.iter().any(|field| {
+            let field = field.split('.');
+            field.clone().count() <= path.len()
+                && field.rev().zip(path.iter().rev()).all(|(f, p)| f == p)
+        })
+    }
+}
+
+fn placeholder(value: &str) -> &'static str {
+    if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        "1970-01-01"
+    } else if value.starts_with("http://") || value.starts_with("https://") {
+        "https://example.com/redacted"
+    } else {
+        "REDACTED"
+    }
+}
+
+/// Saves every request and its response to a cassette file.
+///
+/// The file is rewritten after every response, so nothing is lost if the
+/// process is interrupted.
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    redactions: Redactions,
+    cassette: Mutex<Cassette>,
+}
+
+impl Recorder {
+    /// Record to a new cassette at `path`, replacing any existing one.
+    pub fn new<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let recorder = Self {
+            path: path.into(),
+            redactions: Redactions::default(),
+            cassette: Mutex::default(),
+        };
+        recorder.save(&Cassette::default())?;
+        Ok(recorder)
+    }
+
+    /// Fields to redact. Defaults to [`Redactions::default`].
+    pub fn with_redactions(mut self, redactions: Redactions) -> Self {
+        self.redactions = redactions;
+        self
+    }
+
+    fn save(&self, cassette: &Cassette) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(&self.path)?;
+        serde_json::to_writer_pretty(file, cassette)?;
+        Ok(())
+    }
+}
+
+impl Middleware for Recorder {
+    fn handle<'a>(
+        &'a self,
+        request: reqwest::Request,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        async move {
+            let recorded_request = RecordedRequest::new(&request);
+            let response = next.run(request).await?;
+
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .filter(|(name, _)| RECORDED_HEADERS.contains(&name.as_str()))
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_owned()))
+                })
+                .collect();
+            let body = response.text().await?;
+
+            let recorded = RecordedResponse {
+                status,
+                headers,
+                body: self.redactions.apply(body.clone()),
+            };
+            // The caller gets the response as it was, not redacted.
+            let unredacted = RecordedResponse {
+                body,
+                ..recorded.clone()
+            };
+
+            let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+            cassette.interactions.push(Interaction {
+                request: recorded_request,
+                response: recorded,
+            });
+            self.save(&cassette)?;
+            unredacted.into_response()
+        }
+        .boxed_local()
+    }
+}
+
+/// Answers requests from a cassette, without sending them.
+///
+/// Each recorded response is served once, in the order they were recorded
+/// for the same method and route. Requests without a remaining recorded
+/// response fail with [`Error::Cassette`].
+#[derive(Debug)]
+pub struct Replayer {
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    /// Replay the cassette at `path`.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let file = std::fs::File::open(path.into())?;
+        let cassette: Cassette = serde_json::from_reader(file)?;
+        Ok(Self::new(cassette))
+    }
+
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: Mutex::new(cassette.interactions.into_iter().map(Some).collect()),
+        }
+    }
+}
+
+impl Middleware for Replayer {
+    fn handle<'a>(
+        &'a self,
+        request: reqwest::Request,
+        _next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        let request = RecordedRequest::new(&request);
+        let mut interactions = self.interactions.lock().unwrap_or_else(|e| e.into_inner());
+        let interaction = interactions
+            .iter_mut()
+            .find(|i| matches!(i, Some(i) if i.request == request))
+            .and_then(Option::take);
+        let result = match interaction {
+            Some(interaction) => interaction.response.into_response(),
+            None => Err(Error::Cassette(format!(
+                "no recorded response for {} {}",
+                request.method, request.route
+            ))),
+        };
+        async move { result }.boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::devices::BatteryLevel, util::TempPath, Client};
+
+    /// Answers every request with a device list.
+    struct Fitbit;
+
+    impl Middleware for Fitbit {
+        fn handle<'a>(
+            &'a self,
+            _request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+            let body = r#"
+[
+{
+    "battery": "High",
+    "batteryLevel": 100,
+    "deviceVersion": "Aria",
+    "id": "Y1PFEJZGGX8QFYTV",
+    "lastSyncTime": "2015-07-27T07:14:34.000",
+    "mac": "A1B2C3D4E5F6",
+    "type": "SCALE"
+}
+]
+            "#;
+            let response = http::Response::builder()
+                .header("content-type", "application/json")
+                .header("fitbit-rate-limit-remaining", "149")
+                .body(body)
+                .unwrap();
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let file = TempPath(
+            std::env::temp_dir().join(format!("fitbit-rs-cassette-{}.json", std::process::id())),
+        );
+        let path = &file.0;
+
+        let client = Client::new("id", "secret")
+            .with_middleware(Recorder::new(path).unwrap())
+            .with_middleware(Fitbit);
+        let recorded = client.devices().get_devices(Some("ABC123")).await.unwrap();
+        assert_eq!(recorded[0].extra["mac"], "A1B2C3D4E5F6");
+
+        let cassette = std::fs::read_to_string(path).unwrap();
+        assert!(cassette.contains("/1/user/-/devices.json"));
+        assert!(!cassette.contains("ABC123"));
+        assert!(!cassette.contains("A1B2C3D4E5F6"));
+
+        let client = Client::new("id", "secret").with_middleware(Replayer::open(path).unwrap());
+        let replayed = client.devices().get_devices(None).await.unwrap();
+        assert_eq!(replayed[0].battery, BatteryLevel::High);
+        assert_eq!(replayed[0].extra["mac"], "REDACTED");
+        assert_eq!(client.rate_limit(), None);
+
+        let err = client.devices().get_devices(None).await.unwrap_err();
+        assert!(matches!(err, Error::Cassette(_)));
+    }
+
+    #[test]
+    fn redacts_by_path() {
+        let body = r#"
+{
+    "user": {
+        "fullName": "Jane Doe",
+        "dateOfBirth": "1990-05-17",
+        "avatar": "https://static0.fitbit.com/images/profile/avatar.png",
+        "topBadges": [{"name": "Top Daily Step"}]
+    },
+    "data": [
+        {
+            "type": "ranked-user",
+            "id": "ABC123",
+            "relationships": {"user": {"data": {"type": "person", "id": "ABC123"}}}
+        }
+    ],
+    "included": [
+        {"type": "person", "id": "ABC123", "attributes": {"name": "John Doe"}}
+    ],
+    "apiSubscriptions": [{"ownerId": "ABC123", "subscriberId": "1", "subscriptionId": "320"}],
+    "devices": [{"id": "123456789", "mac": "A1B2C3D4E5F6"}],
+    "activities": [{"logId": 1234, "name": "Walk"}],
+    "heartRateZones": [{"name": "Fat Burn"}]
+}
+        "#;
+        let redacted: serde_json::Value =
+            serde_json::from_str(&Redactions::default().apply(body.to_owned())).unwrap();
+        assert_eq!(redacted["user"]["fullName"], "REDACTED");
+        assert_eq!(redacted["user"]["dateOfBirth"], "1970-01-01");
+        assert_eq!(redacted["user"]["avatar"], "https://example.com/redacted");
+        assert_eq!(redacted["user"]["topBadges"][0]["name"], "Top Daily Step");
+        assert_eq!(redacted["data"][0]["id"], "REDACTED");
+        let related = &redacted["data"][0]["relationships"]["user"]["data"];
+        assert_eq!(related["id"], "REDACTED");
+        assert_eq!(redacted["included"][0]["id"], "REDACTED");
+        assert_eq!(redacted["included"][0]["attributes"]["name"], "REDACTED");
+        assert_eq!(redacted["apiSubscriptions"][0]["ownerId"], "REDACTED");
+        assert_eq!(redacted["apiSubscriptions"][0]["subscriberId"], "REDACTED");
+        assert_eq!(redacted["apiSubscriptions"][0]["subscriptionId"], "320");
+        assert_eq!(redacted["devices"][0]["id"], "123456789");
+        assert_eq!(redacted["devices"][0]["mac"], "REDACTED");
+        assert_eq!(redacted["activities"][0]["logId"], 1234);
+        assert_eq!(redacted["heartRateZones"][0]["name"], "Fat Burn");
+
+        let redacted: serde_json::Value = serde_json::from_str(
+            &Redactions::none()
+                .with_fields(["name"])
+                .apply(body.to_owned()),
+        )
+        .unwrap();
+        assert_eq!(redacted["heartRateZones"][0]["name"], "REDACTED");
+        assert_eq!(redacted["user"]["fullName"], "Jane Doe");
+    }
+}
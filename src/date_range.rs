@@ -0,0 +1,206 @@
+//! Inclusive date ranges, relative periods, and splitting ranges up into
+//! chunks the Fitbit API accepts.
+
+use std::{fmt, future::Future};
+
+use chrono::{Duration, NaiveDate, Utc};
+
+use crate::{error::Error, Result};
+
+/// An inclusive range of days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl DateRange {
+    /// Range from `start` to `end`, both inclusive. Fails if `start` is
+    /// after `end`.
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self> {
+        if start > end {
+            return Err(Error::InvalidDateRange(start, end));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Range from `start` up to and including today (UTC).
+    pub fn until_today(start: NaiveDate) -> Result<Self> {
+        Self::new(start, Utc::now().naive_utc().date())
+    }
+
+    /// The `days` days up to and including `end`.
+    pub fn ending_at(end: NaiveDate, days: u32) -> Self {
+        let start = end - Duration::days(i64::from(days.max(1)) - 1);
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    /// Number of days in the range, counting both ends.
+    pub fn days(&self) -> u32 {
+        (self.end - self.start).num_days() as u32 + 1
+    }
+
+    /// Every day in the range, in order.
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> {
+        self.start.iter_days().take(self.days() as usize)
+    }
+
+    /// Split the range into consecutive, ordered ranges of at most
+    /// `max_days` days each.
+    pub fn chunks(&self, max_days: u32) -> impl Iterator<Item = DateRange> {
+        let max_days = i64::from(max_days.max(1));
+        let end = self.end;
+        let mut next = Some(self.start);
+
+        std::iter::from_fn(move || {
+            let start = next?;
+            let chunk_end = (start + Duration::days(max_days - 1)).min(end);
+            next = (chunk_end < end).then(|| chunk_end + Duration::days(1));
+            Some(DateRange {
+                start,
+                end: chunk_end,
+            })
+        })
+    }
+}
+
+impl fmt::Display for DateRange {
+    /// Formats as the `{start}/{end}` path segment used by the API.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            self.start.format("%Y-%m-%d"),
+            self.end.format("%Y-%m-%d")
+        )
+    }
+}
+
+/// A period of time ending at a given date, as accepted by the time series
+/// endpoints.
+///
+/// Not every endpoint supports every period. Handlers return
+/// [`Error::UnsupportedPeriod`] for periods their endpoint rejects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    OneDay,
+    SevenDays,
+    ThirtyDays,
+    OneWeek,
+    OneMonth,
+    ThreeMonths,
+    SixMonths,
+    OneYear,
+    /// All available data.
+    Max,
+}
+
+impl Period {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::OneDay => "1d",
+            Period::SevenDays => "7d",
+            Period::ThirtyDays => "30d",
+            Period::OneWeek => "1w",
+            Period::OneMonth => "1m",
+            Period::ThreeMonths => "3m",
+            Period::SixMonths => "6m",
+            Period::OneYear => "1y",
+            Period::Max => "max",
+        }
+    }
+
+    /// Fail with [`Error::UnsupportedPeriod`] unless `self` is one of
+    /// `supported`.
+    pub(crate) fn check(self, supported: &[Period]) -> Result<Self> {
+        if supported.contains(&self) {
+            Ok(self)
+        } else {
+            Err(Error::UnsupportedPeriod(self))
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Call `fetch` for each chunk of at most `max_days` days in `range`, one
+/// after another, and concatenate the results in date order.
+pub(crate) async fn fetch_chunked<T, F, Fut>(
+    range: DateRange,
+    max_days: u32,
+    mut fetch: F,
+) -> Result<Vec<T>>
+where
+    F: FnMut(DateRange) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let mut items = Vec::new();
+    for chunk in range.chunks(max_days) {
+        items.extend(fetch(chunk).await?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rejects_reversed_range() {
+        assert!(DateRange::new(date("2022-01-02"), date("2022-01-01")).is_err());
+    }
+
+    #[test]
+    fn days() {
+        let range = DateRange::new(date("2022-01-01"), date("2022-01-31")).unwrap();
+        assert_eq!(range.days(), 31);
+        assert_eq!(DateRange::ending_at(date("2022-01-31"), 31), range);
+    }
+
+    #[test]
+    fn chunks() {
+        let range = DateRange::new(date("2022-01-01"), date("2022-03-05")).unwrap();
+        let chunks: Vec<_> = range.chunks(31).collect();
+        assert_eq!(
+            chunks,
+            vec![
+                DateRange::new(date("2022-01-01"), date("2022-01-31")).unwrap(),
+                DateRange::new(date("2022-02-01"), date("2022-03-03")).unwrap(),
+                DateRange::new(date("2022-03-04"), date("2022-03-05")).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_chunk() {
+        let range = DateRange::new(date("2022-01-01"), date("2022-01-01")).unwrap();
+        assert_eq!(range.chunks(31).collect::<Vec<_>>(), vec![range]);
+        assert_eq!(range.to_string(), "2022-01-01/2022-01-01");
+    }
+
+    #[test]
+    fn unsupported_period() {
+        let supported = [Period::OneDay, Period::SevenDays];
+        assert_eq!(Period::OneDay.check(&supported).unwrap(), Period::OneDay);
+        assert!(matches!(
+            Period::Max.check(&supported),
+            Err(Error::UnsupportedPeriod(Period::Max))
+        ));
+    }
+}
@@ -1,15 +1,62 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
 use thiserror::Error;
 
+use crate::Period;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("failed to deserialize JSON")]
     Serde(#[from] serde_json::Error),
+    #[error("Fitbit API returned {status}: {body}")]
+    Api {
+        status: reqwest::StatusCode,
+        /// Response body, usually a JSON list of errors.
+        body: String,
+    },
     #[error("HTTP error")]
     Reqwest(#[from] reqwest::Error),
     #[error("failed to read/write file")]
     Io(#[from] std::io::Error),
+    #[error("failed to write CSV")]
+    Csv(#[from] csv::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error")]
+    Sqlite(#[from] rusqlite::Error),
     #[error("error parsing URL")]
     Url(#[from] url::ParseError),
+    #[error("invalid date range: {0} is after {1}")]
+    InvalidDateRange(NaiveDate, NaiveDate),
+    #[error("period {0} is not supported by this endpoint")]
+    UnsupportedPeriod(Period),
+    #[error("request failed after {attempts} attempts")]
+    Retried {
+        attempts: u32,
+        /// Error of the last attempt.
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("rate limit exceeded, resets in {0:?}")]
+    RateLimited(Duration),
+    #[error("no client for user {0}")]
+    UnknownUser(String),
+    #[error("OAuth2 error: {0}")]
+    OAuth(String),
+    #[error("no cached token")]
+    NoCachedToken,
+    #[error("failed to import {path}")]
+    Import {
+        path: String,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("the export directory holds data of user {0}")]
+    ExportUser(String),
+    #[error("cassette error: {0}")]
+    Cassette(String),
+    #[error("invalid webhook signature")]
+    InvalidSignature,
     #[error("unknown data store error")]
     Unknown,
 }
@@ -0,0 +1,830 @@
+//! Export a user's data to a directory of JSON Lines or CSV files.
+//!
+//! Every [`Resource`] is written to its own file(s), with one flat row per
+//! record. Dated resources are fetched in chunks; after each chunk the
+//! progress is saved to `.progress.json` in the output directory. Running
+//! the same export again resumes after the last completed chunk, so an
+//! interrupted export doesn't have to start over. A directory only ever
+//! holds one user's data: exporting another user's into it is an error.
+//!
+//! Requests are paced by the user's rate limit: when it runs out, the export
+//! waits for it to reset.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        devices::{BatteryLevel, Device, DeviceType},
+        sleep::list::SleepLevel,
+        user::profile::Gender,
+    },
+    rate_limit, Client, DateRange, Error, Result,
+};
+
+/// Name of the file progress is saved to, inside the output directory.
+const PROGRESS_FILE: &str = ".progress.json";
+
+/// Output file format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    /// One JSON object per line.
+    #[default]
+    JsonLines,
+    /// Comma separated values with a header row.
+    Csv,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::JsonLines => "jsonl",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// A kind of data which can be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Profile,
+    Devices,
+    Weight,
+    Fat,
+    Activity,
+    Sleep,
+    HeartRate,
+    Food,
+    Water,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 9] = [
+        Resource::Profile,
+        Resource::Devices,
+        Resource::Weight,
+        Resource::Fat,
+        Resource::Activity,
+        Resource::Sleep,
+        Resource::HeartRate,
+        Resource::Food,
+        Resource::Water,
+    ];
+
+    /// Names of the files the resource is written to, without extension.
+    pub fn files(&self) -> &'static [&'static str] {
+        match self {
+            Resource::Profile => &["profile"],
+            Resource::Devices => &["devices"],
+            Resource::Weight => &["weight"],
+            Resource::Fat => &["fat"],
+            Resource::Activity => &["activity"],
+            Resource::Sleep => &["sleep", "sleep_stages"],
+            Resource::HeartRate => &["heart_rate", "heart_rate_zones"],
+            Resource::Food => &["food"],
+            Resource::Water => &["water"],
+        }
+    }
+
+    /// Days fetched per chunk, or `None` for resources which aren't dated.
+    fn chunk_days(&self) -> Option<u32> {
+        match self {
+            Resource::Profile | Resource::Devices => None,
+            Resource::Weight | Resource::Fat => Some(31),
+            Resource::Sleep => Some(100),
+            Resource::HeartRate => Some(365),
+            Resource::Activity | Resource::Food | Resource::Water => Some(1),
+        }
+    }
+}
+
+/// What has been exported so far.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Progress {
+    /// Encoded ID of the user whose data is exported.
+    #[serde(default)]
+    user: Option<String>,
+    range: Option<(NaiveDate, NaiveDate)>,
+    format: Format,
+    /// Last exported day per resource.
+    done: BTreeMap<Resource, NaiveDate>,
+    /// Length of each file after the last completed chunk.
+    files: BTreeMap<String, u64>,
+}
+
+/// Exports a user's data to a directory.
+pub struct Exporter<'client> {
+    client: &'client Client,
+    dir: PathBuf,
+    range: DateRange,
+    format: Format,
+    resources: Vec<Resource>,
+    user_id: Option<String>,
+}
+
+impl<'client> Exporter<'client> {
+    /// Export every resource within `range` to `dir` as JSON Lines.
+    pub fn new<P>(client: &'client Client, dir: P, range: DateRange) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            client,
+            dir: dir.into(),
+            range,
+            format: Format::default(),
+            resources: Resource::ALL.to_vec(),
+            user_id: None,
+        }
+    }
+
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_resources(mut self, resources: &[Resource]) -> Self {
+        self.resources = resources.to_vec();
+        self
+    }
+
+    /// Export another user's data instead of the authenticated user's.
+    pub fn with_user_id(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_owned());
+        self
+    }
+
+    /// Run the export, resuming a previous run with the same range and
+    /// format if there is one.
+    pub async fn run(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let user = self.user().await?;
+        let mut progress = self.load_progress(&user)?;
+        self.truncate_files(&progress)?;
+
+        for &resource in &self.resources {
+            let done = progress.done.get(&resource).copied();
+            let Some(chunk_days) = resource.chunk_days() else {
+                if done.is_none() {
+                    self.export(&mut progress, resource, self.range).await?;
+                }
+                continue;
+            };
+
+            let start = done.map_or(self.range.start(), |d| d + chrono::Duration::days(1));
+            let Ok(remaining) = DateRange::new(start, self.range.end()) else {
+                continue;
+            };
+            for chunk in remaining.chunks(chunk_days) {
+                self.export(&mut progress, resource, chunk).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Export one chunk of a resource and save the progress.
+    async fn export(
+        &self,
+        progress: &mut Progress,
+        resource: Resource,
+        range: DateRange,
+    ) -> Result<()> {
+        let client = self.client;
+        let user_id = self.user_id.as_deref();
+
+        match resource {
+            Resource::Profile => {
+                let user = rate_limit::throttled(client, || async move {
+                    client.user().get_profile(user_id).await
+                })
+                .await?;
+                let row = ProfileRow {
+                    encoded_id: &user.encoded_id,
+                    display_name: &user.display_name,
+                    full_name: &user.full_name,
+                    gender: &user.gender,
+                    date_of_birth: user.date_of_birth,
+                    member_since: user.member_since,
+                    height: user.height,
+                    weight: user.weight,
+                    timezone: &user.timezone,
+                    offset_from_utc_millis: user.offset_from_utc,
+                    locale: &user.locale,
+                };
+                self.write(progress, "profile", &[row])?;
+            }
+            Resource::Devices => {
+                let devices = rate_limit::throttled(client, || async move {
+                    client.devices().get_devices(user_id).await
+                })
+                .await?;
+                let rows: Vec<_> = devices
+                    .iter()
+                    .map(|d| DeviceRow {
+                        id: &d.id,
+                        device_version: &d.device_version,
+                        device_type: &d.ty,
+                        battery: &d.battery,
+                        battery_level: d.battery_percentage,
+                        last_sync_time: d.last_sync_time,
+                    })
+                    .collect();
+                self.write(progress, "devices", &rows)?;
+            }
+            Resource::Weight => {
+                let logs = rate_limit::throttled(client, || async move {
+                    client
+                        .body_time_series()
+                        .get_weight_time_series_by_date_range(range, user_id)
+                        .await
+                })
+                .await?;
+                let rows: Vec<_> = logs
+                    .iter()
+                    .map(|l| WeightRow {
+                        log_id: l.log_id,
+                        date: l.date,
+                        time: l.time,
+                        weight: l.weight,
+                        bmi: l.bmi,
+                        source: l.source.as_deref(),
+                    })
+                    .collect();
+                self.write(progress, "weight", &rows)?;
+            }
+            Resource::Fat => {
+                let logs = rate_limit::throttled(client, || async move {
+                    client
+                        .body_time_series()
+                        .get_body_fat_time_series_by_date_range(range, user_id)
+                        .await
+                })
+                .await?;
+                let rows: Vec<_> = logs
+                    .iter()
+                    .map(|l| FatRow {
+                        log_id: l.log_id,
+                        date: l.date,
+                        time: l.time,
+                        fat: l.fat,
+                        source: &l.source,
+                    })
+                    .collect();
+                self.write(progress, "fat", &rows)?;
+            }
+            Resource::Activity => {
+                let date = range.start();
+                let response = rate_limit::throttled(client, || async move {
+                    client
+                        .activity()
+                        .get_daily_activity_summary(Some(date), user_id)
+                        .await
+                })
+                .await?;
+                let s = &response.summary;
+                let row = ActivityRow {
+                    date,
+                    steps: s.steps,
+                    calories_out: s.calories_out,
+                    activity_calories: s.activity_calories,
+                    calories_bmr: s.calories_bmr,
+                    distance: s
+                        .distances
+                        .iter()
+                        .find(|d| d.activity == "total")
+                        .map(|d| d.distance),
+                    floors: s.floors,
+                    elevation: s.elevation,
+                    sedentary_minutes: s.sedentary_minutes,
+                    lightly_active_minutes: s.lightly_active_minutes,
+                    fairly_active_minutes: s.fairly_active_minutes,
+                    very_active_minutes: s.very_active_minutes,
+                };
+                self.write(progress, "activity", &[row])?;
+            }
+            Resource::Sleep => {
+                let sleep = rate_limit::throttled(client, || async move {
+                    client
+                        .sleep()
+                        .get_sleep_log_by_date_range(range, user_id)
+                        .await
+                })
+                .await?;
+                let rows: Vec<_> = sleep
+                    .iter()
+                    .map(|s| SleepRow {
+                        log_id: s.log_id,
+                        date_of_sleep: s.date_of_sleep,
+                        start_time: s.start_time,
+                        end_time: s.end_time,
+                        duration_millis: s.duration_millis,
+                        minutes_asleep: s.minutes_asleep,
+                        minutes_awake: s.minutes_awake,
+                        time_in_bed: s.time_in_bed,
+                        efficiency: s.efficiency,
+                        is_main_sleep: s.is_main_sleep,
+                        sleep_type: &s.type_,
+                    })
+                    .collect();
+                let stages: Vec<_> = sleep
+                    .iter()
+                    .flat_map(|s| {
+                        s.levels.data.iter().map(|d| SleepStageRow {
+                            log_id: s.log_id,
+                            date_time: d.date_time,
+                            level: &d.level,
+                            seconds: d.seconds,
+                        })
+                    })
+                    .collect();
+                self.write(progress, "sleep", &rows)?;
+                self.write(progress, "sleep_stages", &stages)?;
+            }
+            Resource::HeartRate => {
+                let days = rate_limit::throttled(client, || async move {
+                    client
+                        .heart_rate()
+                        .get_heart_rate_time_series_by_date_range(range, user_id)
+                        .await
+                })
+                .await?;
+                let rows: Vec<_> = days
+                    .iter()
+                    .map(|d| HeartRateRow {
+                        date: d.date_time,
+                        resting_heart_rate: d.value.resting_heart_rate,
+                    })
+                    .collect();
+                let zones: Vec<_> = days
+                    .iter()
+                    .flat_map(|d| {
+                        d.value.heart_rate_zones.iter().map(|z| HeartRateZoneRow {
+                            date: d.date_time,
+                            name: &z.name,
+                            min: z.min,
+                            max: z.max,
+                            minutes: z.minutes,
+                            calories_out: z.calories_out,
+                        })
+                    })
+                    .collect();
+                self.write(progress, "heart_rate", &rows)?;
+                self.write(progress, "heart_rate_zones", &zones)?;
+            }
+            Resource::Food => {
+                let date = range.start();
+                let response = rate_limit::throttled(client, || async move {
+                    client.nutrition().get_food_log(Some(date), user_id).await
+                })
+                .await?;
+                let rows: Vec<_> = response
+                    .foods
+                    .iter()
+                    .map(|f| FoodRow {
+                        log_id: f.log_id,
+                        date: f.log_date,
+                        food_id: f.logged_food.food_id,
+                        name: &f.logged_food.name,
+                        meal_type_id: f.logged_food.meal_type_id,
+                        amount: f.logged_food.amount,
+                        unit: &f.logged_food.unit.name,
+                        calories: f.logged_food.calories,
+                    })
+                    .collect();
+                self.write(progress, "food", &rows)?;
+            }
+            Resource::Water => {
+                let date = range.start();
+                let response = rate_limit::throttled(client, || async move {
+                    client.nutrition().get_water_log(Some(date), user_id).await
+                })
+                .await?;
+                let rows: Vec<_> = response
+                    .water
+                    .iter()
+                    .map(|w| WaterRow {
+                        log_id: w.log_id,
+                        date,
+                        amount: w.amount,
+                    })
+                    .collect();
+                self.write(progress, "water", &rows)?;
+            }
+        }
+
+        progress.done.insert(resource, range.end());
+        self.save_progress(progress)
+    }
+
+    fn path(&self, file: &str) -> PathBuf {
+        self.dir.join(format!("{file}.{}", self.format.extension()))
+    }
+
+    /// Append `rows` to `file` and record its new length.
+    fn write<T: Serialize>(&self, progress: &mut Progress, file: &str, rows: &[T]) -> Result<()> {
+        let path = self.path(file);
+        let handle = OpenOptions::new().create(true).append(true).open(&path)?;
+        let is_empty = handle.metadata()?.len() == 0;
+
+        match self.format {
+            Format::JsonLines => {
+                let mut writer = BufWriter::new(handle);
+                for row in rows {
+                    serde_json::to_writer(&mut writer, row)?;
+                    writer.write_all(b"\n")?;
+                }
+                writer.flush()?;
+            }
+            Format::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(is_empty)
+                    .from_writer(handle);
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                writer.flush()?;
+            }
+        }
+
+        progress
+            .files
+            .insert(file.to_owned(), std::fs::metadata(&path)?.len());
+        Ok(())
+    }
+
+    /// The encoded ID of the user whose data is exported.
+    async fn user(&self) -> Result<String> {
+        if let Some(user_id) = self.user_id.clone().or_else(|| self.client.auth.user_id()) {
+            return Ok(user_id);
+        }
+        let client = self.client;
+        let profile =
+            rate_limit::throttled(
+                client,
+                || async move { client.user().get_profile(None).await },
+            )
+            .await?;
+        Ok(profile.encoded_id)
+    }
+
+    /// Load the progress of a previous run, or start over if there is none
+    /// or it was for a different range or format. Fails if the directory
+    /// holds another user's export.
+    fn load_progress(&self, user: &str) -> Result<Progress> {
+        let range = Some((self.range.start(), self.range.end()));
+        let progress = match File::open(self.dir.join(PROGRESS_FILE)) {
+            Ok(file) => serde_json::from_reader(file)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Progress::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        if progress.range.is_some() && progress.user.as_deref() != Some(user) {
+            return Err(Error::ExportUser(progress.user.unwrap_or_default()));
+        }
+        if progress.range == range && progress.format == self.format {
+            Ok(progress)
+        } else {
+            Ok(Progress {
+                user: Some(user.to_owned()),
+                range,
+                format: self.format,
+                ..Progress::default()
+            })
+        }
+    }
+
+    fn save_progress(&self, progress: &Progress) -> Result<()> {
+        // Replace the file in one step, so a crash can't leave it half
+        // written.
+        let path = self.dir.join(PROGRESS_FILE);
+        let tmp = path.with_extension("json.tmp");
+        let file = File::create(&tmp)?;
+        serde_json::to_writer_pretty(&file, progress)?;
+        file.sync_all()?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Drop anything written after the last completed chunk.
+    fn truncate_files(&self, progress: &Progress) -> Result<()> {
+        for resource in &self.resources {
+            for file in resource.files() {
+                let path = self.path(file);
+                let len = progress.files.get(*file).copied().unwrap_or(0);
+                truncate(&path, len)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn truncate(path: &Path, len: u64) -> Result<()> {
+    match OpenOptions::new().write(true).open(path) {
+        Ok(file) => Ok(file.set_len(len)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Serialize)]
+struct ProfileRow<'a> {
+    encoded_id: &'a str,
+    display_name: &'a str,
+    full_name: &'a str,
+    gender: &'a Gender,
+    date_of_birth: NaiveDate,
+    member_since: NaiveDate,
+    height: f32,
+    weight: f32,
+    timezone: &'a str,
+    offset_from_utc_millis: i64,
+    locale: &'a str,
+}
+
+#[derive(Serialize)]
+struct DeviceRow<'a> {
+    id: &'a str,
+    device_version: &'a Device,
+    device_type: &'a DeviceType,
+    battery: &'a BatteryLevel,
+    battery_level: usize,
+    last_sync_time: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+struct WeightRow<'a> {
+    log_id: u64,
+    date: NaiveDate,
+    time: NaiveTime,
+    weight: f32,
+    bmi: f32,
+    source: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct FatRow<'a> {
+    log_id: u64,
+    date: NaiveDate,
+    time: NaiveTime,
+    fat: f32,
+    source: &'a str,
+}
+
+#[derive(Serialize)]
+struct ActivityRow {
+    date: NaiveDate,
+    steps: usize,
+    calories_out: usize,
+    activity_calories: usize,
+    calories_bmr: usize,
+    /// Total distance.
+    distance: Option<f32>,
+    floors: usize,
+    elevation: f32,
+    sedentary_minutes: usize,
+    lightly_active_minutes: usize,
+    fairly_active_minutes: usize,
+    very_active_minutes: usize,
+}
+
+#[derive(Serialize)]
+struct SleepRow<'a> {
+    log_id: u64,
+    date_of_sleep: NaiveDate,
+    start_time: NaiveDateTime,
+    end_time: NaiveDateTime,
+    duration_millis: u64,
+    minutes_asleep: u64,
+    minutes_awake: u64,
+    time_in_bed: u64,
+    efficiency: u64,
+    is_main_sleep: bool,
+    sleep_type: &'a str,
+}
+
+#[derive(Serialize)]
+struct SleepStageRow<'a> {
+    log_id: u64,
+    date_time: NaiveDateTime,
+    level: &'a SleepLevel,
+    seconds: u64,
+}
+
+#[derive(Serialize)]
+struct HeartRateRow {
+    date: NaiveDate,
+    resting_heart_rate: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct HeartRateZoneRow<'a> {
+    date: NaiveDate,
+    name: &'a str,
+    min: usize,
+    max: usize,
+    minutes: Option<usize>,
+    calories_out: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct FoodRow<'a> {
+    log_id: u64,
+    date: NaiveDate,
+    food_id: u64,
+    name: &'a str,
+    meal_type_id: usize,
+    amount: f32,
+    unit: &'a str,
+    calories: usize,
+}
+
+#[derive(Serialize)]
+struct WaterRow {
+    log_id: u64,
+    date: NaiveDate,
+    amount: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use futures_util::FutureExt;
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::{
+        middleware::{LocalBoxFuture, Middleware, Next},
+        RetryPolicy,
+    };
+
+    /// Answers weight requests with the next of `responses`, given as status
+    /// and remaining rate limit, logging the requests it got. Successful
+    /// responses hold one log on the first day of the requested range.
+    struct Scripted {
+        responses: Mutex<Vec<(u16, u32)>>,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Scripted {
+        fn handle<'a>(
+            &'a self,
+            request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, crate::Result<reqwest::Response>> {
+            let path = request.url().path().to_owned();
+            let mut log = self.log.lock().unwrap();
+            log.push(path.clone());
+            let (status, remaining) = self.responses.lock().unwrap().remove(0);
+
+            let date = path.split('/').nth(8).unwrap();
+            let body = format!(
+                r#"{{"weight":[{{"bmi":23.57,"date":"{date}","logId":{},"source":"API","time":"08:00:00","weight":73}}]}}"#,
+                log.len()
+            );
+            let response = http::Response::builder()
+                .status(status)
+                .header("fitbit-rate-limit-limit", "150")
+                .header("fitbit-rate-limit-remaining", remaining)
+                .header("fitbit-rate-limit-reset", "1")
+                .body(body)
+                .unwrap();
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    /// A client authorized as `user_id`, answered by [`Scripted`].
+    fn scripted_client(
+        user_id: &str,
+        responses: &[(u16, u32)],
+        log: &Arc<Mutex<Vec<String>>>,
+    ) -> Client {
+        let client = Client::new("id", "secret")
+            .with_retry_policy(RetryPolicy::default().with_max_attempts(1))
+            .with_middleware(Scripted {
+                responses: Mutex::new(responses.to_vec()),
+                log: log.clone(),
+            });
+        client.auth.set_test_token(user_id);
+        client
+    }
+
+    #[tokio::test]
+    async fn run_and_resume() {
+        let dir =
+            std::env::temp_dir().join(format!("fitbit-rs-export-resume-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let range =
+            DateRange::new("2022-01-01".parse().unwrap(), "2022-03-31".parse().unwrap()).unwrap();
+        let route = |range| format!("/1/user/-/body/log/weight/date/{range}.json");
+        let chunks: Vec<_> = range.chunks(31).collect();
+        let lines = || {
+            std::fs::read_to_string(dir.join("weight.jsonl"))
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        // The first chunk exhausts the rate limit, the second one is rate
+        // limited once, and the third one fails.
+        let log = Arc::default();
+        let client = scripted_client("A", &[(200, 0), (429, 0), (200, 149), (500, 149)], &log);
+        let start = Instant::now();
+        let err = Exporter::new(&client, &dir, range)
+            .with_resources(&[Resource::Weight])
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Api {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                ..
+            }
+        ));
+        // Waited for the reset before the second chunk and after the 429.
+        assert!(start.elapsed() >= Duration::from_millis(1500));
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                route(chunks[0]),
+                route(chunks[1]),
+                route(chunks[1]),
+                route(chunks[2])
+            ]
+        );
+        assert_eq!(lines(), 2);
+
+        let log = Arc::default();
+        let client = scripted_client("A", &[(200, 149)], &log);
+        Exporter::new(&client, &dir, range)
+            .with_resources(&[Resource::Weight])
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), [route(chunks[2])]);
+        assert_eq!(lines(), 3);
+        assert!(!dir.join(".progress.json.tmp").exists());
+
+        // Another user's data doesn't end up in the same files.
+        let log = Arc::default();
+        let client = scripted_client("B", &[], &log);
+        let err = Exporter::new(&client, &dir, range)
+            .with_resources(&[Resource::Weight])
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ExportUser(ref user) if user == "A"));
+        assert!(log.lock().unwrap().is_empty());
+        assert_eq!(lines(), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn progress_round_trip() {
+        let mut progress = Progress {
+            range: Some(("2022-01-01".parse().unwrap(), "2022-12-31".parse().unwrap())),
+            format: Format::Csv,
+            ..Progress::default()
+        };
+        progress
+            .done
+            .insert(Resource::HeartRate, "2022-06-30".parse().unwrap());
+        progress.files.insert("heart_rate".to_owned(), 1024);
+
+        let json = serde_json::to_string(&progress).unwrap();
+        let progress: Progress = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            progress.done[&Resource::HeartRate],
+            "2022-06-30".parse::<NaiveDate>().unwrap()
+        );
+        assert_eq!(progress.files["heart_rate"], 1024);
+    }
+
+    #[test]
+    fn truncate_to_progress() {
+        let dir = std::env::temp_dir().join(format!("fitbit-rs-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("weight.csv");
+        std::fs::write(&path, "log_id,date\n1,2022-01-01\n2,2022-01-02\n").unwrap();
+
+        truncate(&path, 25).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "log_id,date\n1,2022-01-01\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+//! `tracing` spans for API requests.
+
+use std::time::Duration;
+
+use tracing::{field::Empty, Span};
+
+use crate::{Error, RateLimit, Result};
+
+/// A span for `request`, with the status and latency left to
+/// [`record_outcome`].
+pub(crate) fn request_span(request: &reqwest::Request) -> Span {
+    let (route, user_id) = route_template(request.url().path());
+    tracing::info_span!(
+        "fitbit.request",
+        method = %request.method(),
+        route,
+        user_id,
+        status = Empty,
+        latency_ms = Empty,
+        rate_limit_remaining = Empty,
+    )
+}
+
+pub(crate) fn record_outcome(
+    span: &Span,
+    result: &Result<reqwest::Response>,
+    latency: Duration,
+    rate_limit: Option<RateLimit>,
+) {
+    span.record("latency_ms", latency.as_millis() as u64);
+    if let Some(rate_limit) = rate_limit {
+        span.record("rate_limit_remaining", rate_limit.remaining);
+    }
+    let mut error = match result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            return;
+        }
+        Err(e) => e,
+    };
+    while let Error::Retried { source, .. } = error {
+        error = source;
+    }
+    match error {
+        Error::Api { status, .. } => {
+            span.record("status", status.as_u16());
+        }
+        Error::RateLimited(_) => {
+            span.record("status", 429);
+        }
+        _ => {}
+    }
+    let _enter = span.enter();
+    tracing::warn!(error = %error, "request failed");
+}
+
+/// Split `path` into a low cardinality route, with IDs and dates replaced by
+/// placeholders, and the user ID. The first segment is the API version.
+pub(crate) fn route_template(path: &str) -> (String, String) {
+    let mut user_id = String::from("-");
+    let mut previous = "";
+    let segments: Vec<_> = path
+        .split('/')
+        .enumerate()
+        .map(|(i, segment)| {
+            let (stem, extension) = match segment.strip_suffix(".json") {
+                Some(stem) => (stem, ".json"),
+                None => (segment, ""),
+            };
+            let placeholder = if previous == "user" {
+                user_id = stem.to_owned();
+                Some("{user_id}")
+            } else if is_date(stem) {
+                Some("{date}")
+            } else if i > 1 && !stem.is_empty() && stem.bytes().all(|b| b.is_ascii_digit()) {
+                Some("{id}")
+            } else {
+                None
+            };
+            previous = segment;
+            match placeholder {
+                Some(placeholder) => format!("{placeholder}{extension}"),
+                None => segment.to_owned(),
+            }
+        })
+        .collect();
+    (segments.join("/"), user_id)
+}
+
+fn is_date(segment: &str) -> bool {
+    segment.len() == 10 && chrono::NaiveDate::parse_from_str(segment, "%Y-%m-%d").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates() {
+        assert_eq!(
+            route_template("/1/user/-/body/log/weight/date/2022-03-01/2022-03-31.json"),
+            (
+                "/1/user/{user_id}/body/log/weight/date/{date}/{date}.json".to_owned(),
+                "-".to_owned()
+            )
+        );
+        assert_eq!(
+            route_template("/1.2/user/ABC123/sleep/14147621400.json"),
+            (
+                "/1.2/user/{user_id}/sleep/{id}.json".to_owned(),
+                "ABC123".to_owned()
+            )
+        );
+    }
+}
@@ -1,16 +1,52 @@
-use std::path::PathBuf;
+use std::{cell::Cell, path::PathBuf, time::Duration};
 
-use api::{body, body_time_series};
+use api::{
+    activity, body, body_time_series, devices, friends, heart_rate, nutrition, sleep,
+    subscriptions, user,
+};
+use cache::{CachePolicy, ResponseCache};
+use middleware::{Authorize, Middleware, Next, RecordRateLimit};
 use oauth::Auth;
-use reqwest::{StatusCode, Url};
+use reqwest::{header::ACCEPT_LANGUAGE, StatusCode, Url};
 use serde::{de::DeserializeOwned, Serialize};
+use units::UnitSystem;
 
 mod api;
+mod date_range;
 mod error;
+#[cfg(feature = "tracing")]
+mod instrument;
 mod oauth;
+mod pool;
+mod rate_limit;
+mod raw;
+mod retry;
 mod util;
 
+pub use date_range::{DateRange, Period};
+pub use error::Error;
+pub use pool::ClientPool;
+pub use rate_limit::RateLimit;
+pub use raw::WithRaw;
+pub use retry::RetryPolicy;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod cassette;
+pub mod export;
+pub mod middleware;
 pub mod models;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod strict;
+pub mod sync;
+pub mod synthetic;
+pub mod takeout;
+pub mod timezone;
+pub mod units;
+pub mod webhook;
+pub mod weight_import;
 
 /// A convenience type with a default error type of [`Error`].
 pub type Result<T, E = error::Error> = std::result::Result<T, E>;
@@ -20,12 +56,26 @@ pub struct Client {
     auth: Auth,
     client: reqwest::Client,
     base_url: Url,
+    unit_system: UnitSystem,
+    rate_limit: Cell<Option<RateLimit>>,
+    strict: bool,
+    retry_policy: RetryPolicy,
+    response_cache: Option<Box<dyn ResponseCache>>,
+    cache_policy: CachePolicy,
+    middleware: Vec<Box<dyn Middleware>>,
 }
 
 impl Client {
     pub fn new(client_id: &str, client_secret: &str) -> Self {
         Self {
             base_url: Url::parse(BASE_URL).unwrap(),
+            unit_system: UnitSystem::default(),
+            rate_limit: Cell::new(None),
+            strict: false,
+            retry_policy: RetryPolicy::default(),
+            response_cache: None,
+            cache_policy: CachePolicy::default(),
+            middleware: Vec::new(),
             auth: Auth::new(client_id.to_owned(), client_secret.to_owned(), None),
             client: reqwest::ClientBuilder::new()
                 .user_agent("fitbit-rs")
@@ -41,17 +91,107 @@ impl Client {
         self
     }
 
-    pub async fn auth_interactive(self) -> Self {
-        self.auth.auth_interactive().await;
+    /// Unit system to request measurements in. Defaults to
+    /// [`UnitSystem::Metric`].
+    pub fn with_unit_system(mut self, unit_system: UnitSystem) -> Self {
+        self.unit_system = unit_system;
+        self
+    }
+
+    pub fn unit_system(&self) -> UnitSystem {
+        self.unit_system
+    }
+
+    /// When to retry failed requests. Defaults to [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Add `middleware` to the end of the chain, see [`middleware`].
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Cache `GET` responses for past days in `cache`, see [`cache`].
+    pub fn with_response_cache<C>(mut self, cache: C) -> Self
+    where
+        C: ResponseCache + 'static,
+    {
+        self.response_cache = Some(Box::new(cache));
+        self
+    }
+
+    /// How long to cache responses. Defaults to [`CachePolicy::default`].
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
         self
     }
 
-    pub fn body(&self) -> body::BodyHandler {
+    /// Fail on fields and enum values the models don't know about, instead
+    /// of keeping them in [`models::Extra`] and `Unknown` variants. Meant
+    /// for tests; see [`strict`].
+    pub fn with_strict_mode(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Authorize through the browser, unless a token is cached. `open_url`
+    /// is given the authorization URL to show to the user, who is then
+    /// redirected to a local server on port 8080.
+    pub async fn auth_interactive(self, open_url: impl FnOnce(&Url)) -> Result<Self> {
+        self.auth.auth_interactive(open_url).await?;
+        Ok(self)
+    }
+
+    /// Load a previously cached token without falling back to the
+    /// interactive flow. Requires [`Client::with_cache`].
+    pub fn auth_from_cache(self) -> Result<Self> {
+        self.auth.auth_from_cache()?;
+        Ok(self)
+    }
+
+    /// Rate limit state reported by the most recent response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.get()
+    }
+
+    // Handler methods are mirrored by `blocking::Client`. Add new ones to
+    // its `handlers!` list as well; a test there checks none are missing.
+    pub fn activity(&self) -> activity::ActivityHandler<'_> {
+        activity::ActivityHandler::new(self)
+    }
+    pub fn body(&self) -> body::BodyHandler<'_> {
         body::BodyHandler::new(self)
     }
-    pub fn body_time_series(&self) -> body_time_series::BodyTimeSeriesHandler {
+    pub fn body_time_series(&self) -> body_time_series::BodyTimeSeriesHandler<'_> {
         body_time_series::BodyTimeSeriesHandler::new(self)
     }
+    pub fn devices(&self) -> devices::DevicesHandler<'_> {
+        devices::DevicesHandler::new(self)
+    }
+    pub fn friends(&self) -> friends::FriendsHandler<'_> {
+        friends::FriendsHandler::new(self)
+    }
+    pub fn heart_rate(&self) -> heart_rate::HeartRateHandler<'_> {
+        heart_rate::HeartRateHandler::new(self)
+    }
+    pub fn nutrition(&self) -> nutrition::NutritionHandler<'_> {
+        nutrition::NutritionHandler::new(self)
+    }
+    pub fn sleep(&self) -> sleep::SleepHandler<'_> {
+        sleep::SleepHandler::new(self)
+    }
+    pub fn subscriptions(&self) -> subscriptions::SubscriptionsHandler<'_> {
+        subscriptions::SubscriptionsHandler::new(self)
+    }
+    pub fn user(&self) -> user::UserHandler<'_> {
+        user::UserHandler::new(self)
+    }
 }
 
 impl Client {
@@ -60,18 +200,63 @@ impl Client {
     }
 }
 
+/// How the body of a request is encoded.
+///
+/// Most of Fitbit's write endpoints take form or query parameters rather
+/// than JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Json,
+    /// `application/x-www-form-urlencoded`.
+    Form,
+    /// Query parameters.
+    Query,
+}
+
+/// Requests to arbitrary routes.
+///
+/// These are an escape hatch for endpoints without a handler. They go
+/// through the same authentication, token refresh, rate limit tracking and
+/// error handling as the handlers. Deserialize into [`serde_json::Value`] to
+/// get the response as is, or into [`WithRaw`] to get both a typed value and
+/// its JSON:
+///
+/// ```no_run
+/// # async fn run(client: fitbit_rs::Client) -> fitbit_rs::Result<()> {
+/// use fitbit_rs::{models::devices, WithRaw};
+///
+/// let value: serde_json::Value = client
+///     .get("/1/user/-/activities/favorite.json", None::<&()>)
+///     .await?;
+/// let devices: WithRaw<devices::Response> =
+///     client.get("/1/user/-/devices.json", None::<&()>).await?;
+/// # Ok(())
+/// # }
+/// ```
 impl Client {
-    /// Send a `POST` request to `route` with an optional body, returning the
-    /// body of the response.
-    pub(crate) async fn post<P: Serialize + ?Sized, R: DeserializeOwned>(
+    /// Send a `POST` request to `route` with an optional JSON body, returning
+    /// the body of the response.
+    pub async fn post<P: Serialize + ?Sized, R: DeserializeOwned>(
         &self,
         route: &str,
         body: Option<&P>,
     ) -> Result<R> {
-        let response = self._post(self.absolute_url(route)?, body).await?;
-        let text = response.text().await?;
-        let json = serde_json::from_str(&text)?;
-        Ok(json)
+        self.post_encoded(route, body, Encoding::Json).await
+    }
+
+    /// Send a `POST` request to `route` with an optional body sent as
+    /// `encoding`, returning the body of the response.
+    pub async fn post_encoded<P: Serialize + ?Sized, R: DeserializeOwned>(
+        &self,
+        route: &str,
+        body: Option<&P>,
+        encoding: Encoding,
+    ) -> Result<R> {
+        let response = self
+            ._post(self.absolute_url(route)?, body, encoding)
+            .await?;
+        self.deserialize(&response.text().await?)
     }
 
     /// Send a `POST` request with no additional pre/post-processing.
@@ -79,11 +264,16 @@ impl Client {
         &self,
         url: impl reqwest::IntoUrl,
         body: Option<&P>,
+        encoding: Encoding,
     ) -> Result<reqwest::Response> {
         let mut request = self.client.post(url);
 
         if let Some(body) = body {
-            request = request.json(body);
+            request = match encoding {
+                Encoding::Json => request.json(body),
+                Encoding::Form => request.form(body),
+                Encoding::Query => request.query(body),
+            };
         }
 
         self.execute(request).await
@@ -91,15 +281,13 @@ impl Client {
 
     /// Send a `GET` request to `route` with optional query parameters,
     /// returning the body of the response.
-    pub(crate) async fn get<R, P>(&self, route: &str, parameters: Option<&P>) -> Result<R>
+    pub async fn get<R, P>(&self, route: &str, parameters: Option<&P>) -> Result<R>
     where
         P: Serialize + ?Sized,
         R: DeserializeOwned,
     {
         let response = self._get(self.absolute_url(route)?, parameters).await?;
-        let text = response.text().await?;
-        let json = serde_json::from_str(&text)?;
-        Ok(json)
+        self.deserialize(&response.text().await?)
     }
 
     /// Send a `GET` request with no additional post-processing.
@@ -119,15 +307,13 @@ impl Client {
 
     /// Send a `PATCH` request to `route` with optional query parameters,
     /// returning the body of the response.
-    pub(crate) async fn patch<R, B>(&self, route: &str, body: Option<&B>) -> Result<R>
+    pub async fn patch<R, B>(&self, route: &str, body: Option<&B>) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
         let response = self._patch(self.absolute_url(route)?, body).await?;
-        let text = response.text().await?;
-        let json = serde_json::from_str(&text)?;
-        Ok(json)
+        self.deserialize(&response.text().await?)
     }
 
     /// Send a `PATCH` request with no additional post-processing.
@@ -147,15 +333,13 @@ impl Client {
 
     /// Send a `PUT` request to `route` with optional query parameters,
     /// returning the body of the response.
-    pub(crate) async fn put<R, B>(&self, route: &str, body: Option<&B>) -> Result<R>
+    pub async fn put<R, B>(&self, route: &str, body: Option<&B>) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
         let response = self._put(self.absolute_url(route)?, body).await?;
-        let text = response.text().await?;
-        let json = serde_json::from_str(&text)?;
-        Ok(json)
+        self.deserialize(&response.text().await?)
     }
 
     /// Send a `PATCH` request with no additional post-processing.
@@ -175,15 +359,16 @@ impl Client {
 
     /// Send a `DELETE` request to `route` with optional query parameters,
     /// returning the body of the response.
-    pub(crate) async fn delete<R, A, P>(&self, route: &str, parameters: Option<&P>) -> Result<R>
+    ///
+    /// Fitbit usually answers deletes with an empty body, which deserializes
+    /// like `null`, e.g. into `()`.
+    pub async fn delete<R, P>(&self, route: &str, parameters: Option<&P>) -> Result<R>
     where
         P: Serialize + ?Sized,
         R: DeserializeOwned,
     {
         let response = self._delete(self.absolute_url(route)?, parameters).await?;
-        let text = response.text().await?;
-        let json = serde_json::from_str(&text)?;
-        Ok(json)
+        self.deserialize(&response.text().await?)
     }
 
     /// Send a `DELETE` request with no additional post-processing.
@@ -206,33 +391,127 @@ impl Client {
         &self,
         request: reqwest::RequestBuilder,
     ) -> Result<reqwest::Response> {
-        let authed_request = {
-            let token_ref = self.auth.get_token();
-            let token = token_ref
-                .as_deref()
-                .expect("Couldn't get token. Ensure you've called interactive_auth() first.");
-            request.try_clone().unwrap().bearer_auth(token)
+        let request = match self.unit_system.accept_language() {
+            Some(language) => request.header(ACCEPT_LANGUAGE, language),
+            None => request,
         };
 
-        let result = authed_request.send().await?;
-        let status = result.status();
-        if !status.is_success() {
-            match status {
-                StatusCode::UNAUTHORIZED => {
-                    println!("Refreshing token...");
-                    let token = self.auth.refresh_token().await;
-                    if let Some(token) = token.as_deref() {
-                        let authed_request = request.bearer_auth(&token);
-                        let result = authed_request.send().await?;
-                        return Ok(result);
-                    }
-                }
-                _ => {
-                    eprintln!("Error: {}", result.text().await.unwrap());
-                    std::process::exit(1);
+        let request = request.build()?;
+
+        let cache_entry = self.cache_entry(&request);
+        if let Some((cache, key, _)) = &cache_entry {
+            if let Some(body) = cache.get(key) {
+                return Ok(http::Response::new(body).into());
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        let (span, start) = (
+            instrument::request_span(&request),
+            std::time::Instant::now(),
+        );
+        let result = self.run_middleware(request);
+        #[cfg(feature = "tracing")]
+        let result = tracing::Instrument::instrument(result, span.clone());
+        let result = result.await;
+        #[cfg(feature = "tracing")]
+        instrument::record_outcome(&span, &result, start.elapsed(), self.rate_limit());
+
+        match (cache_entry, result) {
+            (Some((cache, key, ttl)), Ok(response)) => {
+                let mut parts = http::Response::builder().status(response.status());
+                if let Some(headers) = parts.headers_mut() {
+                    headers.extend(response.headers().clone());
                 }
-            };
+                let body = response.bytes().await?.to_vec();
+                cache.put(&key, body.clone(), ttl);
+                Ok(parts
+                    .body(body)
+                    .expect("status and headers came from a valid response")
+                    .into())
+            }
+            (_, result) => result,
+        }
+    }
+
+    /// The cache, key and TTL to use for `request`, if it should be cached.
+    fn cache_entry(
+        &self,
+        request: &reqwest::Request,
+    ) -> Option<(&dyn ResponseCache, String, Duration)> {
+        let cache = self.response_cache.as_deref()?;
+        if request.method() != reqwest::Method::GET {
+            return None;
+        }
+        let ttl = self.cache_policy.ttl(request.url().path())?;
+        // `-` is whoever the client is authorized as, so it can't be part of
+        // a key shared with other clients.
+        let url = match request.url().as_str().split_once("/user/-/") {
+            Some((before, after)) => format!("{before}/user/{}/{after}", self.auth.user_id()?),
+            None => request.url().to_string(),
         };
-        Ok(result)
+        let key = match request.headers().get(ACCEPT_LANGUAGE) {
+            Some(language) => format!("{url} {}", language.to_str().unwrap_or("")),
+            None => url,
+        };
+        Some((cache, key, ttl))
+    }
+
+    /// Run `request` through the middleware chain.
+    async fn run_middleware(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        let builtin: [&dyn Middleware; 3] = [&self.retry_policy, &Authorize, &RecordRateLimit];
+        let chain: Vec<&dyn Middleware> = builtin
+            .into_iter()
+            .chain(self.middleware.iter().map(|m| m.as_ref()))
+            .collect();
+        let next = Next {
+            client: self,
+            chain: &chain,
+        };
+
+        match next.run(request).await? {
+            response if response.status().is_success() => Ok(response),
+            response if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = self.rate_limit().and_then(|r| r.exhausted_for());
+                Err(Error::RateLimited(retry_after.unwrap_or_default()))
+            }
+            response => Err(api_error(response).await),
+        }
+    }
+
+    /// Deserialize a response body, honouring [`Client::with_strict_mode`].
+    /// An empty body is treated as `null`.
+    pub(crate) fn deserialize<R: DeserializeOwned>(&self, text: &str) -> Result<R> {
+        let text = if text.trim().is_empty() { "null" } else { text };
+        Ok(strict::with(self.strict, || serde_json::from_str(text))?)
+    }
+
+    pub(crate) fn record_rate_limit(&self, response: &reqwest::Response) {
+        if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+            self.rate_limit.set(Some(rate_limit));
+        }
+    }
+}
+
+/// Turn an unsuccessful response into an [`Error::Api`].
+pub(crate) async fn api_error(response: reqwest::Response) -> Error {
+    let status = response.status();
+    match response.text().await {
+        Ok(body) => Error::Api { status, body },
+        Err(e) => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_empty_body() {
+        let client = Client::new("id", "secret");
+        let () = client.deserialize("").unwrap();
+        let none: Option<u32> = client.deserialize("\n").unwrap();
+        assert_eq!(none, None);
+        assert!(client.deserialize::<Vec<u32>>("").is_err());
     }
 }
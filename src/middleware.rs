@@ -0,0 +1,259 @@
+//! Hooks into every request the [`Client`] sends.
+//!
+//! A [`Middleware`] gets each outgoing request before it is sent and can
+//! modify it, send it any number of times through [`Next::run`], or answer
+//! it itself. Middleware run in the order they were added, after the
+//! built-in ones: the [`RetryPolicy`](crate::RetryPolicy), authorization,
+//! and rate limit tracking. Each attempt of a retried request thus goes
+//! through the added middleware again.
+//!
+//! Responses with error statuses are passed through middleware as they are,
+//! and only turned into [`Error`](crate::Error)s once they leave the chain.
+//!
+//! ```
+//! use fitbit_rs::middleware::{LocalBoxFuture, Middleware, Next};
+//! use reqwest::header::HeaderValue;
+//!
+//! struct Tenant(HeaderValue);
+//!
+//! impl Middleware for Tenant {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         mut request: reqwest::Request,
+//!         next: Next<'a>,
+//!     ) -> LocalBoxFuture<'a, fitbit_rs::Result<reqwest::Response>> {
+//!         request.headers_mut().insert("x-tenant", self.0.clone());
+//!         next.run(request)
+//!     }
+//! }
+//!
+//! let client = fitbit_rs::Client::new("client id", "client secret")
+//!     .with_middleware(Tenant(HeaderValue::from_static("acme")));
+//! ```
+
+pub use futures_util::future::LocalBoxFuture;
+use futures_util::FutureExt;
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
+
+use crate::{Client, Result};
+
+/// Inspects or modifies requests and their outcomes.
+pub trait Middleware {
+    /// Handle `request`, usually by passing it on to `next`.
+    fn handle<'a>(
+        &'a self,
+        request: reqwest::Request,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>>;
+}
+
+/// The rest of the middleware chain, ending in actually sending the request.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    pub(crate) client: &'a Client,
+    pub(crate) chain: &'a [&'a dyn Middleware],
+}
+
+impl<'a> Next<'a> {
+    /// Pass `request` on to the next middleware, or send it if this is the
+    /// end of the chain.
+    pub fn run(self, request: reqwest::Request) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        match self.chain.split_first() {
+            Some((middleware, chain)) => middleware.handle(
+                request,
+                Next {
+                    client: self.client,
+                    chain,
+                },
+            ),
+            None => async move { Ok(self.client.client.execute(request).await?) }.boxed_local(),
+        }
+    }
+}
+
+/// Sends the user's token, refreshing it once if it expired.
+pub(crate) struct Authorize;
+
+impl Middleware for Authorize {
+    fn handle<'a>(
+        &'a self,
+        mut request: reqwest::Request,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        async move {
+            // Requests with streaming bodies can't be copied, and so can't
+            // be sent again with a refreshed token.
+            let retry = request.try_clone();
+            authorize(next.client, &mut request);
+            let response = next.run(request).await?;
+
+            let Some(mut retry) = retry else {
+                return Ok(response);
+            };
+            if response.status() != StatusCode::UNAUTHORIZED {
+                return Ok(response);
+            }
+            #[cfg(feature = "tracing")]
+            tracing::info!("refreshing token");
+            if next.client.auth.refresh_token().await.is_none() {
+                return Ok(response);
+            }
+            authorize(next.client, &mut retry);
+            next.run(retry).await
+        }
+        .boxed_local()
+    }
+}
+
+/// Set the bearer token, if there is one. Without one, Fitbit answers with
+/// 401 Unauthorized.
+fn authorize(client: &Client, request: &mut reqwest::Request) {
+    let Some(token) = client.auth.get_token() else {
+        return;
+    };
+    if let Ok(mut value) = HeaderValue::from_str(&format!("Bearer {}", &*token)) {
+        value.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+}
+
+/// Keeps [`Client::rate_limit`] up to date.
+pub(crate) struct RecordRateLimit;
+
+impl Middleware for RecordRateLimit {
+    fn handle<'a>(
+        &'a self,
+        request: reqwest::Request,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        async move {
+            let response = next.run(request).await?;
+            next.client.record_rate_limit(&response);
+            Ok(response)
+        }
+        .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{Error, RetryPolicy};
+
+    /// Answers every request with the next of `statuses`, logging the
+    /// requests it got.
+    struct Canned {
+        statuses: Mutex<Vec<u16>>,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Canned {
+        fn new(statuses: &[u16], log: &Arc<Mutex<Vec<String>>>) -> Self {
+            Self {
+                statuses: Mutex::new(statuses.iter().rev().copied().collect()),
+                log: log.clone(),
+            }
+        }
+    }
+
+    impl Middleware for Canned {
+        fn handle<'a>(
+            &'a self,
+            request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+            let tenant = request.headers().get("x-tenant").cloned();
+            self.log.lock().unwrap().push(format!(
+                "{} {} {:?}",
+                request.method(),
+                request.url().path(),
+                tenant
+            ));
+            let status = self.statuses.lock().unwrap().pop().unwrap();
+            let response = http::Response::builder()
+                .status(status)
+                .body(r#"{"ok":true}"#)
+                .unwrap();
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    struct Tenant;
+
+    impl Middleware for Tenant {
+        fn handle<'a>(
+            &'a self,
+            mut request: reqwest::Request,
+            next: Next<'a>,
+        ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+            request
+                .headers_mut()
+                .insert("x-tenant", HeaderValue::from_static("acme"));
+            next.run(request)
+        }
+    }
+
+    fn canned_client(statuses: &[u16], log: &Arc<Mutex<Vec<String>>>) -> Client {
+        Client::new("id", "secret")
+            .with_retry_policy(RetryPolicy::default().with_backoff(Duration::ZERO, Duration::ZERO))
+            .with_middleware(Tenant)
+            .with_middleware(Canned::new(statuses, log))
+    }
+
+    #[tokio::test]
+    async fn runs_in_order() {
+        let log = Arc::default();
+        let client = canned_client(&[200], &log);
+
+        let value: serde_json::Value = client
+            .get("/1/user/-/profile.json", None::<&()>)
+            .await
+            .unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [r#"GET /1/user/-/profile.json Some("acme")"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_through_middleware() {
+        let log = Arc::default();
+        let client = canned_client(&[503, 502, 200], &log);
+        let value: serde_json::Value = client
+            .get("/1/user/-/devices.json", None::<&()>)
+            .await
+            .unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(log.lock().unwrap().len(), 3);
+
+        let log = Arc::default();
+        let client = canned_client(&[503, 503, 503], &log);
+        let err = client
+            .get::<serde_json::Value, _>("/1/user/-/devices.json", None::<&()>)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Retried { attempts: 3, ref source }
+                if matches!(**source, Error::Api { status: StatusCode::SERVICE_UNAVAILABLE, .. })
+        ));
+
+        let log = Arc::default();
+        let client = canned_client(&[503], &log);
+        let err = client
+            .post::<(), serde_json::Value>("/1/user/-/body/log/weight.json", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Api { .. }));
+        assert_eq!(log.lock().unwrap().len(), 1);
+    }
+}
@@ -1,6 +1,13 @@
+mod extra;
+
+pub use extra::{Extra, Unrecognized};
+
 pub mod activity;
 pub mod body;
 pub mod devices;
+pub mod friends;
 pub mod heart_rate;
+pub mod nutrition;
 pub mod sleep;
+pub mod subscriptions;
 pub mod user;
@@ -1,25 +1,28 @@
 //! Definitions for activity and exercise logs
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 pub mod goals;
 pub mod lifetime_stats;
 pub mod summary;
 pub mod time_series;
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivityLog {
-    activity_id: usize,
-    activity_parent_id: usize,
-    calories: usize,
-    description: String,
-    distance: f32,
-    duration: usize,
-    has_start_time: bool,
-    is_favorite: bool,
-    log_id: usize,
-    name: String,
-    start_time: String,
-    steps: usize,
+    pub activity_id: usize,
+    pub activity_parent_id: usize,
+    pub calories: usize,
+    pub description: String,
+    pub distance: f32,
+    pub duration: usize,
+    pub has_start_time: bool,
+    pub is_favorite: bool,
+    pub log_id: usize,
+    pub name: String,
+    pub start_time: String,
+    pub steps: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
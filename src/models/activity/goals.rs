@@ -2,28 +2,64 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize)]
+use crate::models::Extra;
+
+/// Goal period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+        }
+    }
+}
+
+/// Goals to change. Goals left as `None` are kept as they are. Weekly goals
+/// don't support `active_minutes` or `calories_out`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct Request {
-    pub goals: Goals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_minutes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calories_out: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floors: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub goals: Goals,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Goals {
-    pub calories_out: usize,
+    pub active_minutes: Option<usize>,
+    pub calories_out: Option<usize>,
     pub distance: f32,
     pub floors: usize,
     pub steps: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -38,6 +74,21 @@ mod tests {
 }
         "#;
 
-        let _res: Response = serde_json::from_str(data).unwrap();
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
+
+    #[test]
+    fn serialize_request() {
+        let req = Request {
+            steps: Some(12000),
+            distance: Some(9.5),
+            ..Request::default()
+        };
+        assert_round_trip(&req);
+        assert_eq!(
+            serde_urlencoded::to_string(&req).unwrap(),
+            "distance=9.5&steps=12000"
+        );
     }
 }
@@ -1,49 +1,64 @@
 //! Get lifetime statistics.
 
 use chrono::naive::NaiveDate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+use crate::models::Extra;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub best: Option<BestStats>,
     pub lifetime: LifetimeStats,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BestStats {
     pub total: BestStatsSet,
     pub tracker: BestStatsSet,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BestStatsSet {
     pub distance: BestStat,
     pub floors: BestStat,
     pub steps: BestStat,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BestStat {
     pub date: NaiveDate,
     pub value: f32,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LifetimeStats {
     pub total: LifetimeStatsSet,
     pub tracker: LifetimeStatsSet,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LifetimeStatsSet {
     pub distance: f32,
     pub floors: usize,
     pub steps: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -94,6 +109,7 @@ mod tests {
 }
 		"#;
 
-        let _res: Response = serde_json::from_str(data).unwrap();
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 }
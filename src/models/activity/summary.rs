@@ -1,25 +1,34 @@
 //! Get daily activity summary.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::Extra,
+    units::{Length, UnitSystem},
+};
 
 /// Daily activity summary response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub activities: Vec<super::ActivityLog>,
     pub goals: Goals,
     pub summary: Summary,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Goals {
     #[serde(rename = "caloriesOut")]
     pub calories_out: usize,
     pub distance: f32,
     pub floors: usize,
     pub steps: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
     pub activity_calories: usize,
@@ -35,17 +44,30 @@ pub struct Summary {
     pub sedentary_minutes: usize,
     pub steps: usize,
     pub very_active_minutes: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Distance {
     pub activity: String,
     pub distance: f32,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Distance {
+    /// [`Distance::distance`] as a length, given the unit system it was
+    /// requested in.
+    pub fn distance_in(&self, units: UnitSystem) -> Length {
+        units.distance(self.distance)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -100,6 +122,7 @@ mod tests {
 }
 "#;
 
-        let _res: Response = serde_json::from_str(data).unwrap();
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 }
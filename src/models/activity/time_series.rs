@@ -1,10 +1,12 @@
 //! Get activity time series data.
 
 use chrono::naive::NaiveDate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Resource {
     Calories,
     CaloriesBMR,
@@ -19,22 +21,28 @@ pub enum Resource {
     ActivityCalories,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Entry {
     pub date_time: NaiveDate,
     pub value: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 macro_rules! endpoint {
     ($mod:ident, $rename:expr) => {
         pub mod $mod {
-            use serde::Deserialize;
+            use serde::{Deserialize, Serialize};
+
+            use crate::models::Extra;
 
-            #[derive(Deserialize, Debug)]
+            #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
             pub struct Response {
                 #[serde(rename = $rename)]
                 pub series: Vec<super::Entry>,
+                #[serde(flatten)]
+                pub extra: Extra,
             }
         }
     };
@@ -51,3 +59,27 @@ endpoint!(minutes_lightly_active, "activities-minutesLightlyActive");
 endpoint!(minutes_fairly_active, "activities-minutesFairlyActive");
 endpoint!(minutes_very_active, "activities-minutesVeryActive");
 endpoint!(activity_calories, "activities-activityCalories");
+
+#[cfg(test)]
+mod tests {
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "activities-steps": [
+        {"dateTime": "2019-01-01", "value": "2504"},
+        {"dateTime": "2019-01-02", "value": "3723"}
+    ]
+}
+        "#;
+
+        let res: super::steps::Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(
+            serde_json::to_value(&res).unwrap()["activities-steps"][1]["dateTime"],
+            "2019-01-02"
+        );
+    }
+}
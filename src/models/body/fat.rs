@@ -1,9 +1,14 @@
 //! Body fat logs
 
-use chrono::naive::{NaiveDate, NaiveTime};
+use chrono::{
+    naive::{NaiveDate, NaiveTime},
+    DateTime, FixedOffset,
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::{models::Extra, timezone::UserTimezone};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FatLog {
     pub date: NaiveDate,
@@ -11,11 +16,21 @@ pub struct FatLog {
     pub log_id: u64,
     pub time: NaiveTime,
     pub source: String, // TODO: Device enum
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl FatLog {
+    /// When the body fat was logged, in the user's timezone.
+    pub fn localized_date_time(&self, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(self.date.and_time(self.time))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -29,6 +44,7 @@ mod tests {
 }
         "#;
 
-        let _res: FatLog = serde_json::from_str(data).unwrap();
+        let res: FatLog = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 }
@@ -3,32 +3,43 @@
 use chrono::naive::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::models::Extra;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct WeightGoalContainer {
     pub goal: WeightGoal,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WeightGoal {
     pub start_date: NaiveDate,
     pub start_weight: String,
     pub weight: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FatGoalContainer {
     pub goal: FatGoal,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FatGoal {
     pub fat: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize_weight_goal() {
@@ -41,7 +52,8 @@ mod tests {
     }
 }
         "#;
-        let _res: WeightGoalContainer = serde_json::from_str(data).unwrap();
+        let res: WeightGoalContainer = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 
     #[test]
@@ -53,6 +65,7 @@ mod tests {
     }
 }
         "#;
-        let _res: FatGoalContainer = serde_json::from_str(data).unwrap();
+        let res: FatGoalContainer = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 }
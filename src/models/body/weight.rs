@@ -1,9 +1,18 @@
 //! Weight logging API
 
-use chrono::naive::{NaiveDate, NaiveTime};
-use serde::Deserialize;
+use chrono::{
+    naive::{NaiveDate, NaiveTime},
+    DateTime, FixedOffset,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+use crate::{
+    models::Extra,
+    timezone::UserTimezone,
+    units::{Mass, UnitSystem},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WeightLog {
     pub bmi: f32,
@@ -12,11 +21,27 @@ pub struct WeightLog {
     pub time: NaiveTime,
     pub weight: f32,
     pub source: Option<String>, // TODO: Device enum
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl WeightLog {
+    /// When the weight was logged, in the user's timezone.
+    pub fn localized_date_time(&self, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(self.date.and_time(self.time))
+    }
+
+    /// [`WeightLog::weight`] as a mass, given the unit system it was
+    /// requested in.
+    pub fn weight_in(&self, units: UnitSystem) -> Mass {
+        units.weight(self.weight)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize_get_weight_logs() {
@@ -31,6 +56,11 @@ mod tests {
 }
         "#;
 
-        let _res: WeightLog = serde_json::from_str(data).unwrap();
+        let res: WeightLog = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(
+            serde_json::to_value(&res).unwrap()["logId"],
+            1330991999000u64
+        );
     }
 }
@@ -11,14 +11,16 @@
 //! - Last sync time
 
 use chrono::naive::NaiveDateTime;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Extra, Unrecognized};
 
 /// Expected response container.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response(pub Vec<DeviceInfo>);
 
 /// Information about a particular device.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceInfo {
     /// Rough battery level indication.
@@ -34,29 +36,35 @@ pub struct DeviceInfo {
     /// Product name.
     #[serde(rename = "type")]
     pub ty: DeviceType,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// Rough battery level indication.
-#[derive(Deserialize, Debug, PartialEq, PartialOrd)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, PartialOrd)]
 pub enum BatteryLevel {
     Empty,
     Low,
     Medium,
     High,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
 /// Used to separate different product categories.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DeviceType {
     /// Something you wear on your wrist.
     Tracker,
     /// A smart scale.
     Scale,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
 /// A Fitbit product.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[non_exhaustive]
 pub enum Device {
     Aria,
@@ -74,44 +82,26 @@ pub enum Device {
     MobileTrack,
     Surge,
     Versa,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{cassette::Replayer, util::assert_round_trip, Client};
 
-    #[test]
-    fn deserialize() {
-        let data = r#"
-[
-{
-    "battery": "High",
-    "batteryLevel": 100,
-    "deviceVersion": "Charge HR",
-    "id": "27072629",
-    "lastSyncTime": "2015-07-27T17:01:39.313",
-    "type": "TRACKER"
-},
-{
-    "battery": "Empty",
-    "batteryLevel": 100,
-    "deviceVersion": "MobileTrack",
-    "id": "29559794",
-    "lastSyncTime": "2015-07-19T16:57:59.000",
-    "type": "TRACKER"
-},
-{
-    "battery": "High",
-    "batteryLevel": 100,
-    "deviceVersion": "Aria",
-    "id": "Y1PFEJZGGX8QFYTV",
-    "lastSyncTime": "2015-07-27T07:14:34.000",
-    "type": "SCALE"
-}
-]
-        "#;
+    #[tokio::test]
+    async fn replay() {
+        let cassette = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/devices.json");
+        let client = Client::new("id", "secret").with_middleware(Replayer::open(cassette).unwrap());
 
-        let res: Response = serde_json::from_str(data).unwrap();
-        println!("{:#?}", res);
+        let devices = client.devices().get_devices(None).await.unwrap();
+        assert_round_trip(&Response(devices.clone()));
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[1].battery, BatteryLevel::Empty);
+        assert_eq!(devices[2].ty, DeviceType::Scale);
+        assert_eq!(devices[2].extra["mac"], "REDACTED");
+        assert_eq!(client.rate_limit().unwrap().remaining, 148);
     }
 }
@@ -0,0 +1,133 @@
+//! Types for keeping data the models don't know about yet.
+//!
+//! Fitbit regularly adds fields and enum values without notice. Rather than
+//! failing to parse, models keep unknown fields in an [`Extra`] map and
+//! enums fall back to an `Unknown` variant holding an [`Unrecognized`]
+//! value. Use [`crate::strict`] to turn both into errors instead.
+
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::strict;
+
+/// Fields of an object which aren't part of its model.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct Extra(pub serde_json::Map<String, serde_json::Value>);
+
+impl Deref for Extra {
+    type Target = serde_json::Map<String, serde_json::Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Extra {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Extra {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = serde_json::Map::deserialize(deserializer)?;
+        if strict::is_enabled() && !map.is_empty() {
+            let fields: Vec<_> = map.keys().map(String::as_str).collect();
+            return Err(serde::de::Error::custom(format_args!(
+                "unknown fields: {}",
+                fields.join(", ")
+            )));
+        }
+        Ok(Self(map))
+    }
+}
+
+/// An enum value which isn't part of its model.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Unrecognized(pub String);
+
+impl Unrecognized {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Unrecognized {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if strict::is_enabled() {
+            return Err(serde::de::Error::custom(format_args!(
+                "unknown variant `{value}`"
+            )));
+        }
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            body::fat::FatLog,
+            devices::{BatteryLevel, DeviceInfo},
+        },
+        util::assert_round_trip,
+    };
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "battery": "Full",
+    "batteryLevel": 100,
+    "deviceVersion": "Sense",
+    "features": [],
+    "id": "1234567",
+    "lastSyncTime": "2021-01-01T12:00:00.000",
+    "mac": "ABCDEF123456",
+    "type": "TRACKER"
+}
+        "#;
+
+        let res: DeviceInfo = serde_json::from_str(data).unwrap();
+        assert_eq!(
+            res.battery,
+            BatteryLevel::Unknown(Unrecognized("Full".to_owned()))
+        );
+        assert_eq!(res.extra["mac"], "ABCDEF123456");
+        assert_round_trip(&res);
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(json["battery"], "Full");
+        assert_eq!(json["mac"], "ABCDEF123456");
+
+        assert!(strict_error::<DeviceInfo>(data).contains("BatteryLevel"));
+    }
+
+    #[test]
+    fn strict() {
+        let data = r#"
+{
+    "date":"2012-03-05",
+    "fat":13.5,
+    "logId":1330991999000,
+    "time":"21:20:59",
+    "source":"Aria",
+    "newField":true
+}
+        "#;
+
+        assert!(serde_json::from_str::<FatLog>(data).is_ok());
+        assert!(strict_error::<FatLog>(data).contains("unknown fields: newField"));
+    }
+
+    fn strict_error<T: serde::de::DeserializeOwned + std::fmt::Debug>(data: &str) -> String {
+        match strict::from_str::<T>(data) {
+            Err(crate::Error::Serde(err)) => err.to_string(),
+            other => panic!("expected a deserialization error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! # Friends APIs
+//!
+//! The friends endpoints follow the [JSON:API](https://jsonapi.org) layout:
+//! every record is wrapped in a [`Resource`] carrying its `type`, `id` and
+//! `attributes`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
+use url::Url;
+
+pub mod leaderboard;
+pub mod list;
+
+/// A JSON:API resource object.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Resource<T> {
+    /// Resource type, e.g. `person` or `ranked-user`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Encoded user ID. Matches [`User::encoded_id`](super::user::profile::User::encoded_id).
+    pub id: String,
+    pub attributes: T,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Public profile information about a friend.
+///
+/// This is the subset of [`User`](super::user::profile::User) which Fitbit
+/// shares with a user's friends.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Person {
+    /// Same as [`User::avatar`](super::user::profile::User::avatar).
+    pub avatar: Url,
+    /// Same as [`User::is_child`](super::user::profile::User::is_child).
+    pub child: bool,
+    /// Whether this person is a friend of the user.
+    pub friend: bool,
+    /// Same as [`User::display_name`](super::user::profile::User::display_name).
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
@@ -0,0 +1,142 @@
+//! Get a user's friends leaderboard.
+//!
+//! The leaderboard ranks the user and their friends by steps over the last 7
+//! days. Ranked entries only reference a user by ID; the matching profiles
+//! are sent alongside in [`Response::included`]. Use [`Response::ranked`] to
+//! join them.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Person, Resource};
+use crate::models::Extra;
+
+/// Get friends leaderboard response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub data: Vec<Resource<RankedUser>>,
+    /// Profiles of every user referenced in [`Response::data`].
+    #[serde(default)]
+    pub included: Vec<Resource<Person>>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Step ranking of a single user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RankedUser {
+    /// Position on the leaderboard, starting at 1. Missing for users without
+    /// any recorded steps.
+    pub step_rank: Option<usize>,
+    /// Total steps over the leaderboard period.
+    pub step_summary: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A leaderboard entry joined with the profile of the ranked user.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub rank: Option<usize>,
+    pub steps: usize,
+    /// `None` if the profile wasn't included in the response.
+    pub person: Option<&'a Person>,
+}
+
+impl Response {
+    /// Leaderboard entries ordered by rank. Unranked users come last.
+    pub fn ranked(&self) -> Vec<Entry<'_>> {
+        let mut entries: Vec<_> = self
+            .data
+            .iter()
+            .map(|ranked| Entry {
+                rank: ranked.attributes.step_rank,
+                steps: ranked.attributes.step_summary,
+                person: self
+                    .included
+                    .iter()
+                    .find(|person| person.id == ranked.id)
+                    .map(|person| &person.attributes),
+            })
+            .collect();
+        entries.sort_by_key(|entry| (entry.rank.is_none(), entry.rank));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "data": [
+        {
+            "type": "ranked-user",
+            "id": "4GV8BT",
+            "attributes": {
+                "step-rank": 2,
+                "step-summary": 41250
+            },
+            "relationships": {
+                "user": {
+                    "data": {
+                        "type": "person",
+                        "id": "4GV8BT"
+                    }
+                }
+            }
+        },
+        {
+            "type": "ranked-user",
+            "id": "228TQ4",
+            "attributes": {
+                "step-rank": 1,
+                "step-summary": 75016
+            },
+            "relationships": {
+                "user": {
+                    "data": {
+                        "type": "person",
+                        "id": "228TQ4"
+                    }
+                }
+            }
+        }
+    ],
+    "included": [
+        {
+            "type": "person",
+            "id": "4GV8BT",
+            "attributes": {
+                "avatar": "https://static0.fitbit.com/images/profile/defaultProfile_100.png",
+                "child": false,
+                "friend": true,
+                "name": "Jane D."
+            }
+        },
+        {
+            "type": "person",
+            "id": "228TQ4",
+            "attributes": {
+                "avatar": "https://static0.fitbit.com/images/profile/defaultProfile_100.png",
+                "child": false,
+                "friend": false,
+                "name": "John D."
+            }
+        }
+    ]
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        let ranked = res.ranked();
+        assert_eq!(ranked[0].steps, 75016);
+        assert_eq!(ranked[0].person.unwrap().name, "John D.");
+        assert_eq!(ranked[1].rank, Some(2));
+    }
+}
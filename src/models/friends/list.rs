@@ -0,0 +1,43 @@
+//! Get a user's friends.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
+
+/// Get friends response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub data: Vec<super::Resource<super::Person>>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "data": [
+        {
+            "type": "person",
+            "id": "4GV8BT",
+            "attributes": {
+                "avatar": "https://static0.fitbit.com/images/profile/defaultProfile_100.png",
+                "child": false,
+                "friend": true,
+                "name": "Jane D."
+            }
+        }
+    ]
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(res.data[0].attributes.name, "Jane D.");
+    }
+}
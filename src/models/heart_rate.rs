@@ -1,11 +1,13 @@
 //! Heart rate data APIs
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 pub mod intraday_time_series;
 pub mod time_series;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HeartRateZone {
     pub calories_out: Option<f32>,
@@ -13,4 +15,6 @@ pub struct HeartRateZone {
     pub min: usize,
     pub minutes: Option<usize>,
     pub name: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
@@ -1,49 +1,106 @@
 //! Intraday heart rate time series data
 
-use chrono::naive::{NaiveDate, NaiveTime};
-use serde::Deserialize;
+use chrono::{
+    naive::{NaiveDate, NaiveTime},
+    DateTime, FixedOffset,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::Extra, timezone::UserTimezone};
+
+/// Interval between intraday data points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailLevel {
+    OneSecond,
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+}
+
+impl DetailLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetailLevel::OneSecond => "1sec",
+            DetailLevel::OneMinute => "1min",
+            DetailLevel::FiveMinutes => "5min",
+            DetailLevel::FifteenMinutes => "15min",
+        }
+    }
+}
+
 /// Heart rate zones time series response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     #[serde(rename = "activities-heart")]
     pub daily_series: Option<Vec<DayEntry>>,
     #[serde(rename = "activities-heart-intraday")]
     pub intraday: Intraday,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DayEntry {
     pub date_time: NaiveDate,
     pub value: Value,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Value {
     pub custom_heart_rate_zones: Vec<super::HeartRateZone>,
     pub heart_rate_zones: Vec<super::HeartRateZone>,
     /// Resting heart rate
     pub value: Option<usize>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Intraday {
     pub dataset: Vec<IntradayEntry>,
     pub dataset_interval: usize,
     pub dataset_type: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Intraday {
+    /// Data points for `date`, with their times in the user's timezone.
+    pub fn localized<'a>(
+        &'a self,
+        date: NaiveDate,
+        tz: &'a UserTimezone,
+    ) -> impl Iterator<Item = (DateTime<FixedOffset>, usize)> + 'a {
+        self.dataset
+            .iter()
+            .map(move |entry| (entry.localized_date_time(date, tz), entry.value))
+    }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct IntradayEntry {
     pub time: NaiveTime,
     pub value: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl IntradayEntry {
+    /// When this data point was recorded on `date`, in the user's timezone.
+    pub fn localized_date_time(&self, date: NaiveDate, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(date.and_time(self.time))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -118,4 +175,47 @@ mod tests {
 
         // let _res: Response = serde_json::from_str(data).unwrap();
     }
+
+    #[test]
+    fn round_trip() {
+        let data = r#"
+{
+    "activities-heart": [
+        {
+            "dateTime": "2019-01-01",
+            "value": {
+                "customHeartRateZones": [],
+                "heartRateZones": [
+                    {
+                        "caloriesOut": 2.3246,
+                        "max": 94,
+                        "min": 30,
+                        "minutes": 2,
+                        "name": "Out of Range"
+                    }
+                ],
+                "value": 64
+            }
+        }
+    ],
+    "activities-heart-intraday": {
+        "dataset": [
+            {
+                "time": "00:00:00",
+                "value": 64
+            },
+            {
+                "time": "00:01:00",
+                "value": 63
+            }
+        ],
+        "datasetInterval": 1,
+        "datasetType": "minute"
+    }
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
 }
@@ -1,56 +1,43 @@
 //! Get heart rate time series data.
 
 use chrono::naive::NaiveDate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[allow(dead_code)]
-/// Possible period ranges for heart rate data.
-pub enum Period {
-    OneDay,
-    SevenDays,
-    ThirtyDays,
-    OneWeek,
-    OneMonth,
-}
-
-impl ToString for Period {
-    fn to_string(&self) -> String {
-        match self {
-            Period::OneDay => "1d".to_string(),
-            Period::SevenDays => "7d".to_string(),
-            Period::ThirtyDays => "30d".to_string(),
-            Period::OneWeek => "1w".to_string(),
-            Period::OneMonth => "1m".to_string(),
-        }
-    }
-}
+use crate::models::Extra;
 
 /// Heart rate zones time series response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     #[serde(rename = "activities-heart")]
     pub series: Vec<DayEntry>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// A heart rate entry for a particular day.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DayEntry {
     pub date_time: NaiveDate,
     pub value: Value,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Value {
     pub custom_heart_rate_zones: Vec<super::HeartRateZone>,
     pub heart_rate_zones: Vec<super::HeartRateZone>,
     pub resting_heart_rate: Option<usize>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::assert_round_trip;
 
     #[test]
     fn deserialize() {
@@ -98,6 +85,7 @@ mod tests {
 }
         "#;
 
-        let _res: Response = serde_json::from_str(data).unwrap();
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
     }
 }
@@ -0,0 +1,4 @@
+//! # Nutrition APIs
+
+pub mod food_log;
+pub mod water_log;
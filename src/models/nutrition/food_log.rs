@@ -0,0 +1,128 @@
+//! Get a user's food log for a day.
+
+use chrono::naive::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
+
+/// Get food log response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub foods: Vec<FoodLog>,
+    pub summary: NutritionalValues,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A food logged by the user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FoodLog {
+    pub is_favorite: bool,
+    pub log_date: NaiveDate,
+    pub log_id: u64,
+    pub logged_food: LoggedFood,
+    pub nutritional_values: Option<NutritionalValues>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedFood {
+    pub access_level: String,
+    pub amount: f32,
+    pub brand: String,
+    pub calories: usize,
+    pub food_id: u64,
+    pub meal_type_id: usize,
+    pub locale: Option<String>,
+    pub name: String,
+    pub unit: Unit,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A unit food amounts are measured in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub id: u64,
+    pub name: String,
+    pub plural: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NutritionalValues {
+    pub calories: f32,
+    pub carbs: f32,
+    pub fat: f32,
+    pub fiber: f32,
+    pub protein: f32,
+    pub sodium: f32,
+    /// Only included in the daily summary.
+    pub water: Option<f32>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "foods": [
+        {
+            "isFavorite": true,
+            "logDate": "2011-06-29",
+            "logId": 1820,
+            "loggedFood": {
+                "accessLevel": "PUBLIC",
+                "amount": 132.57,
+                "brand": "",
+                "calories": 752,
+                "foodId": 18828,
+                "mealTypeId": 4,
+                "locale": "en_US",
+                "name": "Chocolate, Milk",
+                "unit": {
+                    "id": 147,
+                    "name": "gram",
+                    "plural": "grams"
+                },
+                "units": [226, 180, 147, 389]
+            },
+            "nutritionalValues": {
+                "calories": 752,
+                "carbs": 66.5,
+                "fat": 49,
+                "fiber": 0.5,
+                "protein": 12.5,
+                "sodium": 186
+            }
+        }
+    ],
+    "summary": {
+        "calories": 752,
+        "carbs": 66.5,
+        "fat": 49,
+        "fiber": 0.5,
+        "protein": 12.5,
+        "sodium": 186,
+        "water": 0
+    },
+    "goals": {
+        "calories": 2286
+    }
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
+}
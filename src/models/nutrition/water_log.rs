@@ -0,0 +1,57 @@
+//! Get a user's water log for a day.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
+
+/// Get water log response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub water: Vec<WaterLog>,
+    pub summary: Summary,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaterLog {
+    /// In the unit selected by the `Accept-Language` header.
+    pub amount: f32,
+    pub log_id: u64,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// Total water consumed on the day.
+    pub water: f32,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "summary": {
+        "water": 800.0
+    },
+    "water": [
+        {"amount": 500.0, "logId": 950},
+        {"amount": 200.0, "logId": 951},
+        {"amount": 100.0, "logId": 952}
+    ]
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
+}
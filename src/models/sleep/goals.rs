@@ -1,16 +1,20 @@
 //! # Sleep Goals APIs
 
-use chrono::DateTime;
-use serde::Deserialize;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 pub mod get;
 pub mod update;
 
 /// Information about the current sleep goal.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Goal {
     pub min_duration: usize,
-    // TODO: Not sure what the correct timezone is here...
-    pub updated_on: DateTime<chrono::Utc>,
+    /// Sent with its UTC offset, so it needs no [`UserTimezone`](crate::timezone::UserTimezone).
+    pub updated_on: DateTime<FixedOffset>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
@@ -1,17 +1,47 @@
 //! Get a user's current sleep goals.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 /// Get sleep goals response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub consistency: Consistency,
     pub goal: super::Goal,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 // TODO: not sure what this is?
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Consistency {
     pub flow_id: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "consistency": {
+        "flowId": 0
+    },
+    "goal": {
+        "minDuration": 480,
+        "updatedOn": "2017-04-26T15:36:44.000Z"
+    }
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
 }
@@ -2,8 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::Extra;
+
 /// Sleep goal update request.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     /// Target sleep duration in minutes.
@@ -11,7 +13,25 @@ pub struct Request {
 }
 
 /// Sleep goal update response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub goal: super::Goal,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn serialize_request() {
+        let req = Request { min_duration: 480 };
+        assert_round_trip(&req);
+        assert_eq!(
+            serde_json::to_string(&req).unwrap(),
+            r#"{"minDuration":480}"#
+        );
+    }
 }
@@ -1,93 +1,249 @@
 //! Get sleep list for a user.
 
-use serde::Deserialize;
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    DateTime, FixedOffset,
+};
+use serde::{Deserialize, Serialize};
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+use crate::{
+    models::{Extra, Unrecognized},
+    timezone::UserTimezone,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
-    pagination: Pagination,
-    sleep: Vec<Sleep>,
+    pub pagination: Pagination,
+    pub sleep: Vec<Sleep>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Pagination {
-    before_date: Option<String>,
-    after_date: Option<String>,
-    limit: u64,
-    next: String,
-    offset: u64,
-    previous: String,
-    sort: String,
+    pub before_date: Option<String>,
+    pub after_date: Option<String>,
+    pub limit: u64,
+    pub next: String,
+    pub offset: u64,
+    pub previous: String,
+    pub sort: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Sleep {
-    date_of_sleep: String,
+    pub date_of_sleep: NaiveDate,
     #[serde(rename = "duration")]
-    duration_millis: u64,
-    efficiency: u64,
-    end_time: String,
-    info_code: u64,
-    is_main_sleep: bool,
-    levels: Levels,
-    log_id: u64,
-    minutes_after_wakeup: u64,
-    minutes_asleep: u64,
-    minutes_awake: u64,
-    minutes_to_fall_asleep: u64,
-    start_time: String,
-    time_in_bed: u64,
+    pub duration_millis: u64,
+    pub efficiency: u64,
+    pub end_time: NaiveDateTime,
+    pub info_code: u64,
+    pub is_main_sleep: bool,
+    pub levels: Levels,
+    pub log_id: u64,
+    pub minutes_after_wakeup: u64,
+    pub minutes_asleep: u64,
+    pub minutes_awake: u64,
+    pub minutes_to_fall_asleep: u64,
+    pub start_time: NaiveDateTime,
+    pub time_in_bed: u64,
     #[serde(rename = "type")]
-    type_: String,
+    pub type_: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Sleep {
+    /// When the sleep started, in the user's timezone.
+    pub fn localized_start_time(&self, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(self.start_time)
+    }
+
+    /// When the sleep ended, in the user's timezone.
+    pub fn localized_end_time(&self, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(self.end_time)
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Levels {
-    summary: LevelSummary,
-    data: Vec<SleepDataPoint>,
-    short_data: Vec<SleepDataPoint>,
+    pub summary: LevelSummary,
+    pub data: Vec<SleepDataPoint>,
+    /// Short wakes. Only reported for `stages` sleep.
+    #[serde(default)]
+    pub short_data: Vec<SleepDataPoint>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+/// Time spent per level. `stages` sleep reports deep, light, REM and wake,
+/// `classic` sleep reports asleep, awake and restless.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LevelSummary {
-    deep: Summary,
-    light: Summary,
-    rem: Summary,
-    wake: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deep: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rem: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wake: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asleep: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub awake: Option<Summary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restless: Option<Summary>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
-    count: u64,
-    minutes: u64,
-    thirty_day_avg_minutes: u64,
+    pub count: u64,
+    pub minutes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thirty_day_avg_minutes: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SleepDataPoint {
-    date_time: String,
-    level: SleepLevel,
-    seconds: u64,
+    pub date_time: NaiveDateTime,
+    pub level: SleepLevel,
+    pub seconds: u64,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+impl SleepDataPoint {
+    /// When this sleep stage started, in the user's timezone.
+    pub fn localized_date_time(&self, tz: &UserTimezone) -> DateTime<FixedOffset> {
+        tz.localize(self.date_time)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum SleepLevel {
     Wake,
     Light,
     Rem,
     Deep,
+    Asleep,
+    Awake,
+    Restless,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "pagination": {
+        "beforeDate": "2017-03-27",
+        "limit": 1,
+        "next": "https://api.fitbit.com/1.2/user/-/sleep/list.json?offset=1&limit=1&sort=desc&beforeDate=2017-03-27",
+        "offset": 0,
+        "previous": "",
+        "sort": "desc"
+    },
+    "sleep": [
+        {
+            "dateOfSleep": "2017-03-26",
+            "duration": 26340000,
+            "efficiency": 95,
+            "endTime": "2017-03-26T07:30:30.000",
+            "infoCode": 0,
+            "isMainSleep": true,
+            "levels": {
+                "summary": {
+                    "deep": {"count": 5, "minutes": 100, "thirtyDayAvgMinutes": 95},
+                    "light": {"count": 20, "minutes": 200, "thirtyDayAvgMinutes": 210},
+                    "rem": {"count": 6, "minutes": 90, "thirtyDayAvgMinutes": 85},
+                    "wake": {"count": 25, "minutes": 49, "thirtyDayAvgMinutes": 50}
+                },
+                "data": [
+                    {"dateTime": "2017-03-26T00:11:30.000", "level": "wake", "seconds": 30},
+                    {"dateTime": "2017-03-26T00:12:00.000", "level": "light", "seconds": 600}
+                ],
+                "shortData": [
+                    {"dateTime": "2017-03-26T01:00:00.000", "level": "wake", "seconds": 60}
+                ]
+            },
+            "logId": 14147621400,
+            "minutesAfterWakeup": 0,
+            "minutesAsleep": 390,
+            "minutesAwake": 49,
+            "minutesToFallAsleep": 0,
+            "startTime": "2017-03-26T00:11:30.000",
+            "timeInBed": 439,
+            "type": "stages"
+        }
+    ]
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        let tz = UserTimezone::from_offset(FixedOffset::east_opt(3600).unwrap());
+        assert_eq!(
+            res.sleep[0].localized_start_time(&tz).to_rfc3339(),
+            "2017-03-26T00:11:30+01:00"
+        );
+    }
+
+    #[test]
+    fn deserialize_classic() {
+        let data = r#"
+{
+    "dateOfSleep": "2017-03-26",
+    "duration": 1800000,
+    "efficiency": 90,
+    "endTime": "2017-03-26T14:30:00.000",
+    "infoCode": 2,
+    "isMainSleep": false,
+    "levels": {
+        "summary": {
+            "asleep": {"count": 0, "minutes": 27},
+            "awake": {"count": 1, "minutes": 1},
+            "restless": {"count": 1, "minutes": 2}
+        },
+        "data": [
+            {"dateTime": "2017-03-26T14:00:00.000", "level": "asleep", "seconds": 1620},
+            {"dateTime": "2017-03-26T14:27:00.000", "level": "restless", "seconds": 120}
+        ]
+    },
+    "logId": 14147621401,
+    "minutesAfterWakeup": 0,
+    "minutesAsleep": 27,
+    "minutesAwake": 3,
+    "minutesToFallAsleep": 0,
+    "startTime": "2017-03-26T14:00:00.000",
+    "timeInBed": 30,
+    "type": "classic"
+}
+        "#;
+
+        let res: Sleep = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(res.levels.summary.asleep.as_ref().unwrap().minutes, 27);
+        assert_eq!(res.levels.data[1].level, SleepLevel::Restless);
+    }
 }
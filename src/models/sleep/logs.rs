@@ -3,27 +3,59 @@
 //! [More information?](https//dev.fitbit.com/build/reference/web-api/sleep/#
 //! get-sleep-logs)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 
 /// Get sleep logs response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub sleep: Vec<SleepEntry>,
     pub summary: Option<Summary>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// A sleep entry for a particular day.
 ///
 /// There can be multiple entries per day.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct SleepEntry {}
+pub struct SleepEntry {
+    #[serde(flatten)]
+    pub extra: Extra,
+}
 
 /// A sleep summary.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
     pub total_minutes_asleep: usize,
     pub total_sleep_records: usize,
     pub total_time_in_bed: usize,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "sleep": [],
+    "summary": {
+        "totalMinutesAsleep": 0,
+        "totalSleepRecords": 0,
+        "totalTimeInBed": 0
+    }
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
 }
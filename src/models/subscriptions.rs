@@ -0,0 +1,151 @@
+//! # Subscription APIs
+//!
+//! Subscriptions ask Fitbit to notify a webhook whenever a user's data
+//! changes, instead of polling for it. See [`crate::webhook`] for receiving
+//! the notifications.
+
+use chrono::naive::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Extra, Unrecognized};
+
+/// A collection of data which can be subscribed to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CollectionType {
+    Activities,
+    Body,
+    Foods,
+    Sleep,
+    /// The user revoked the application's access.
+    UserRevokedAccess,
+    /// The user deleted their account. Only sent in notifications.
+    DeleteUser,
+    /// Every collection. Only sent in responses, for subscriptions created
+    /// without a collection.
+    User,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
+}
+
+impl CollectionType {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            CollectionType::Activities => "activities",
+            CollectionType::Body => "body",
+            CollectionType::Foods => "foods",
+            CollectionType::Sleep => "sleep",
+            CollectionType::UserRevokedAccess => "userRevokedAccess",
+            CollectionType::DeleteUser => "deleteUser",
+            CollectionType::User => "user",
+            CollectionType::Unknown(value) => value.as_str(),
+        }
+    }
+}
+
+/// A subscription, as returned when creating or listing subscriptions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub collection_type: CollectionType,
+    /// Encoded ID of the subscribed user.
+    pub owner_id: String,
+    pub owner_type: String,
+    /// Subscriber configured in the application settings. Used to route
+    /// notifications to a particular endpoint.
+    pub subscriber_id: String,
+    /// ID chosen when creating the subscription.
+    pub subscription_id: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Get subscription list response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResponse {
+    pub api_subscriptions: Vec<Subscription>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A single change notification sent to the subscriber endpoint.
+///
+/// Notifications only say what changed; the data itself still has to be
+/// fetched with the matching handler.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub collection_type: CollectionType,
+    /// Day on which the data changed.
+    pub date: Option<NaiveDate>,
+    /// Encoded ID of the user whose data changed.
+    pub owner_id: String,
+    pub owner_type: String,
+    pub subscription_id: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize_list() {
+        let data = r#"
+{
+    "apiSubscriptions": [
+        {
+            "collectionType": "user",
+            "ownerId": "184X36",
+            "ownerType": "user",
+            "subscriberId": "1",
+            "subscriptionId": "2345"
+        },
+        {
+            "collectionType": "sleep",
+            "ownerId": "184X36",
+            "ownerType": "user",
+            "subscriberId": "1",
+            "subscriptionId": "2346"
+        }
+    ]
+}
+        "#;
+
+        let res: ListResponse = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(
+            res.api_subscriptions[1].collection_type,
+            CollectionType::Sleep
+        );
+    }
+
+    #[test]
+    fn deserialize_notifications() {
+        let data = r#"
+[
+    {
+        "collectionType": "foods",
+        "date": "2020-06-01",
+        "ownerId": "184X36",
+        "ownerType": "user",
+        "subscriptionId": "2345"
+    },
+    {
+        "collectionType": "userRevokedAccess",
+        "ownerId": "184X36",
+        "ownerType": "user",
+        "subscriptionId": "2345"
+    }
+]
+        "#;
+
+        let res: Vec<Notification> = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(res[1].collection_type, CollectionType::UserRevokedAccess);
+        assert!(res[1].date.is_none());
+    }
+}
@@ -1,21 +1,25 @@
 //! Get a user's badges.
 
 use chrono::naive::NaiveDate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Extra;
 use url::Url;
 
 /// Expected response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     pub badges: Vec<Badge>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// Information about a badge a user has obtained.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Badge {
     pub badge_type: String,
-    pub catagory: Option<String>,
+    pub category: Option<String>,
     pub date_time: NaiveDate,
     pub description: String,
     pub earned_message: Option<String>,
@@ -31,4 +35,47 @@ pub struct Badge {
     pub short_name: String,
     pub times_achieved: usize,
     pub value: i32,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "badges": [
+        {
+            "badgeType": "DAILY_STEPS",
+            "category": "Daily Steps",
+            "dateTime": "2016-07-17",
+            "description": "10,000 steps in a day",
+            "earnedMessage": "Congrats on earning your first Sneakers badge!",
+            "encodedId": "228TQ4",
+            "image100px": "https://static0.fitbit.com/images/badges_new/100px/badge_daily_steps10k.png",
+            "image125px": "https://static0.fitbit.com/images/badges_new/125px/badge_daily_steps10k.png",
+            "image300px": "https://static0.fitbit.com/images/badges_new/300px/badge_daily_steps10k.png",
+            "image50px": "https://static0.fitbit.com/images/badges_new/badge_daily_steps10k.png",
+            "image75px": "https://static0.fitbit.com/images/badges_new/75px/badge_daily_steps10k.png",
+            "marketingDescription": "You've walked 10,000 steps And earned the Sneaker badge!",
+            "mobileDescription": "Congratulations on cruising your way to the first daily step goal.",
+            "name": "Sneakers (10,000 steps in a day)",
+            "shareImage640px": "https://static0.fitbit.com/images/badges_new/386px/shareLocalized/en_US/badge_daily_steps10k.png",
+            "shareText": "I took 10,000 steps and earned the Sneakers badge! #Fitbit",
+            "shortDescription": "10,000 steps",
+            "shortName": "Sneakers",
+            "timesAchieved": 258,
+            "value": 10000
+        }
+    ]
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+    }
 }
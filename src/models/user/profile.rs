@@ -4,17 +4,24 @@ use chrono::naive::NaiveDate;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::{
+    models::{Extra, Unrecognized},
+    units::{Length, Mass, UnitSystem},
+};
+
 pub mod update;
 
 /// A user profile response from a GET or POST request.
 /// Both the get and update APIs send this back as a response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
     /// All information about a user profile.
     pub user: User,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     pub about_me: Option<String>,
@@ -61,72 +68,115 @@ pub struct User {
     pub water_unit_name: String,
     pub weight: f32,
     pub weight_unit: WeightUnit,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl User {
+    /// [`User::height`] as a length, given the unit system it was requested
+    /// in.
+    pub fn height_in(&self, units: UnitSystem) -> Length {
+        units.height(self.height)
+    }
+
+    /// [`User::weight`] as a mass, given the unit system it was requested in.
+    pub fn weight_in(&self, units: UnitSystem) -> Mass {
+        units.weight(self.weight)
+    }
+
+    /// [`User::stride_length_walking`] as a length, given the unit system it
+    /// was requested in.
+    pub fn stride_length_walking_in(&self, units: UnitSystem) -> Length {
+        units.height(self.stride_length_walking)
+    }
+
+    /// [`User::stride_length_running`] as a length, given the unit system it
+    /// was requested in.
+    pub fn stride_length_running_in(&self, units: UnitSystem) -> Length {
+        units.height(self.stride_length_running)
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ClockTimeDisplayFormat {
     #[serde(rename = "12hour")]
     Hours12,
     #[serde(rename = "24hour")]
     Hours24,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum DistanceUnit {
     #[serde(rename = "en_US")]
     FeetInches,
     #[serde(rename = "METRIC")]
     Centimeters,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Gender {
     Male,
     Female,
     NA,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum GlucoseUnit {
     #[serde(rename = "en_US")]
     Imperial,
     Metric,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HeightUnit {
     #[serde(rename = "en_US")]
     Imperial,
     Metric,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum StartDayOfTheWeek {
     Sunday,
     Monday,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum SwimUnit {
     #[serde(rename = "en_US")]
     Yards,
     #[serde(rename = "METRIC")]
     Meters,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum WaterUnit {
     #[serde(rename = "en_US")]
     FluidOuncesOrCups,
     #[serde(rename = "METRIC")]
     Milliliters,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum WeightUnit {
     #[serde(rename = "en_US")]
     Pounds,
@@ -134,4 +184,71 @@ pub enum WeightUnit {
     Stone,
     #[serde(rename = "METRIC")]
     Kilograms,
+    #[serde(untagged)]
+    Unknown(Unrecognized),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "user": {
+        "aboutMe": "",
+        "age": 35,
+        "ambassador": false,
+        "autoStrideEnabled": true,
+        "avatar": "https://static0.fitbit.com/images/profile/defaultProfile_100.png",
+        "avatar150": "https://static0.fitbit.com/images/profile/defaultProfile_150.png",
+        "avatar640": "https://static0.fitbit.com/images/profile/defaultProfile_640.png",
+        "averageDailySteps": 8753,
+        "clockTimeDisplayFormat": "24hour",
+        "corporate": false,
+        "corporateAdmin": false,
+        "country": "DE",
+        "dateOfBirth": "1986-01-01",
+        "displayName": "Jane D.",
+        "displayNameSetting": "name",
+        "distanceUnit": "METRIC",
+        "encodedId": "4GV8BT",
+        "familyGuidanceEnabled": false,
+        "firstName": "Jane",
+        "foodsLocale": "de_DE",
+        "fullName": "Jane Doe",
+        "gender": "FEMALE",
+        "glucoseUnit": "METRIC",
+        "height": 170.0,
+        "heightUnit": "METRIC",
+        "isChild": false,
+        "lastName": "Doe",
+        "locale": "de_DE",
+        "memberSince": "2015-07-27",
+        "mfaEnabled": false,
+        "offsetFromUTCMillis": 7200000,
+        "startDayOfTheWeek": "MONDAY",
+        "strideLengthRunning": 105.5,
+        "strideLengthRunningType": "default",
+        "strideLengthWalking": 70.4,
+        "strideLengthWalkingType": "default",
+        "swimUnit": "METRIC",
+        "timezone": "Europe/Berlin",
+        "waterUnit": "METRIC",
+        "waterUnitName": "ml",
+        "weight": 60.0,
+        "weightUnit": "METRIC"
+    }
+}
+        "#;
+
+        let res: Response = serde_json::from_str(data).unwrap();
+        assert_round_trip(&res);
+        assert_eq!(
+            serde_json::to_value(&res).unwrap()["user"]["offsetFromUTCMillis"],
+            7200000
+        );
+    }
 }
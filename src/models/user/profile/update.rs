@@ -1,10 +1,10 @@
 //! Update a profile for a user.
 
 use chrono::naive::NaiveDate;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Possible user profile options to change.
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     pub gender: Option<super::Gender>,
@@ -29,3 +29,27 @@ pub struct Request {
     pub start_day_of_the_week: Option<super::StartDayOfTheWeek>,
     pub clock_time_display_format: Option<super::ClockTimeDisplayFormat>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::assert_round_trip;
+
+    #[test]
+    fn serialize_request() {
+        let req = Request {
+            stride_length_walking: Some(70.0),
+            weight_unit: Some(super::super::WeightUnit::Kilograms),
+            ..Request::default()
+        };
+        assert_round_trip(&req);
+
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["strideLengthWalking"], 70.0);
+        assert_eq!(json["weightUnit"], "METRIC");
+        assert_eq!(
+            serde_urlencoded::to_string(&req).unwrap(),
+            "strideLengthWalking=70.0&weightUnit=METRIC"
+        );
+    }
+}
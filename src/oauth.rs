@@ -6,18 +6,21 @@
 
 use std::{
     cell::{Ref, RefCell},
-    error::Error,
     ops::Deref,
     path::{Path, PathBuf},
 };
 
 use oauth2::{
-    basic::{BasicClient, BasicTokenType},
+    basic::{
+        BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+        BasicTokenType,
+    },
     reqwest::async_http_client,
     url::Url,
-    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EmptyExtraTokenFields,
-    RefreshToken, Scope, StandardTokenResponse, TokenResponse, TokenUrl,
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, ExtraTokenFields, RefreshToken,
+    Scope, StandardRevocableToken, StandardTokenResponse, TokenResponse, TokenUrl,
 };
+use serde::{Deserialize, Serialize};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::TcpListener,
@@ -25,7 +28,25 @@ use tokio::{
 
 use crate::Result;
 
-type Token = StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType>;
+/// Fields Fitbit sends along with the token.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct FitbitTokenFields {
+    /// Encoded ID of the user who authorized the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user_id: Option<String>,
+}
+
+impl ExtraTokenFields for FitbitTokenFields {}
+
+type Token = StandardTokenResponse<FitbitTokenFields, BasicTokenType>;
+type FitbitClient = oauth2::Client<
+    BasicErrorResponse,
+    Token,
+    BasicTokenType,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
 
 pub(crate) struct Auth {
     client_id: String,
@@ -55,20 +76,36 @@ impl Auth {
         self
     }
 
-    pub(crate) async fn auth_interactive(&self) {
+    pub(crate) async fn auth_interactive(&self, open_url: impl FnOnce(&Url)) -> Result<()> {
         if self.token.borrow().is_some() {
-            return;
+            return Ok(());
         }
 
         if let Some(cache_path) = &self.cache_path {
             if let Ok(token) = read_auth_token(cache_path) {
                 *self.token.borrow_mut() = Some(token);
-                return;
+                return Ok(());
             }
         }
 
-        let token = fetch_token(&self.client_id, &self.client_secret).await;
-        self.save_token(token);
+        let token = fetch_token(&self.client_id, &self.client_secret, open_url).await?;
+        self.save_token(token)
+    }
+
+    /// Load the token from the cache without falling back to the
+    /// interactive flow.
+    pub(crate) fn auth_from_cache(&self) -> Result<()> {
+        if self.token.borrow().is_some() {
+            return Ok(());
+        }
+
+        let cache_path = self
+            .cache_path
+            .as_ref()
+            .ok_or(crate::Error::NoCachedToken)?;
+        let token = read_auth_token(cache_path)?;
+        *self.token.borrow_mut() = Some(token);
+        Ok(())
     }
 
     pub(crate) async fn refresh_token(&self) -> Option<impl Deref<Target = str> + '_> {
@@ -84,19 +121,21 @@ impl Auth {
             .await
         {
             Ok(t) => t,
-            Err(e) => {
-                eprintln!("OAuth2: {}", e);
-                if let Some(source) = e.source() {
-                    eprintln!("source: {}", source);
-                }
-                eprintln!("Invalid refresh token. Clearing.");
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_e, "invalid refresh token, clearing it");
                 if let Some(cache_path) = &self.cache_path {
-                    clear_auth_token(cache_path).unwrap();
+                    // Nothing to clear if the token was never cached.
+                    let _ = clear_auth_token(cache_path);
                 }
-                std::process::exit(1);
+                return None;
             }
         };
-        self.save_token(new_token);
+        if let Err(_e) = self.save_token(new_token) {
+            // The refreshed token is still usable for this session.
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %_e, "couldn't cache the refreshed token");
+        }
         self.get_token()
     }
 
@@ -111,6 +150,27 @@ impl Auth {
         }
     }
 
+    /// Encoded ID of the authorized user, if the token says.
+    pub(crate) fn user_id(&self) -> Option<String> {
+        self.token
+            .borrow()
+            .as_ref()
+            .and_then(|t| t.extra_fields().user_id.clone())
+    }
+
+    /// Pretend `user_id` authorized the client.
+    #[cfg(test)]
+    pub(crate) fn set_test_token(&self, user_id: &str) {
+        let fields = FitbitTokenFields {
+            user_id: Some(user_id.to_owned()),
+        };
+        *self.token.borrow_mut() = Some(Token::new(
+            oauth2::AccessToken::new("token".to_owned()),
+            BasicTokenType::Bearer,
+            fields,
+        ));
+    }
+
     fn get_refresh_token(&self) -> Option<RefreshToken> {
         self.token
             .borrow()
@@ -119,16 +179,18 @@ impl Auth {
             .map(|r| r.to_owned())
     }
 
-    fn save_token(&self, token: Token) {
-        if let Some(cache_path) = &self.cache_path {
-            write_auth_token(&token, cache_path).expect("Couldn't write auth token");
+    /// Use `token` from now on and write it to the cache, if any.
+    fn save_token(&self, token: Token) -> Result<()> {
+        let token = self.token.borrow_mut().insert(token).clone();
+        match &self.cache_path {
+            Some(cache_path) => write_auth_token(&token, cache_path),
+            None => Ok(()),
         }
-        *self.token.borrow_mut() = Some(token);
     }
 }
 
-fn client(client_id: &str, client_secret: &str) -> BasicClient {
-    BasicClient::new(
+fn client(client_id: &str, client_secret: &str) -> FitbitClient {
+    FitbitClient::new(
         ClientId::new(client_id.to_owned()),
         Some(ClientSecret::new(client_secret.to_owned())),
         AuthUrl::new("https://www.fitbit.com/oauth2/authorize".to_string()).unwrap(),
@@ -137,7 +199,11 @@ fn client(client_id: &str, client_secret: &str) -> BasicClient {
 }
 
 /// Get a token via the OAuth 2.0 Implicit Grant Flow
-async fn fetch_token(client_id: &str, client_secret: &str) -> Token {
+async fn fetch_token(
+    client_id: &str,
+    client_secret: &str,
+    open_url: impl FnOnce(&Url),
+) -> Result<Token> {
     let client = client(client_id, client_secret);
 
     // Generate the authorization URL to which we'll redirect the user.
@@ -154,81 +220,69 @@ async fn fetch_token(client_id: &str, client_secret: &str) -> Token {
         .add_scope(Scope::new("weight".to_string()))
         .url();
 
-    println!("Open the following in your browser: {}", authorize_url);
-
-    let listener = TcpListener::bind("0.0.0.0:8080").await.unwrap();
-    if let Ok((mut stream, _)) = listener.accept().await {
-        let code;
-        let state;
-        {
-            let mut reader = BufReader::new(&mut stream);
-
-            let mut request_line = String::new();
-            reader.read_line(&mut request_line).await.unwrap();
-
-            let redirect_url = request_line.split_whitespace().nth(1).unwrap();
-            let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
-
-            let code_pair = url
-                .query_pairs()
-                .find(|pair| {
-                    let &(ref key, _) = pair;
-                    key == "code"
-                })
-                .unwrap();
-
-            let (_, value) = code_pair;
-            code = AuthorizationCode::new(value.into_owned());
-
-            let state_pair = url
-                .query_pairs()
-                .find(|pair| {
-                    let &(ref key, _) = pair;
-                    key == "state"
-                })
-                .unwrap();
-
-            let (_, value) = state_pair;
-            state = CsrfToken::new(value.into_owned());
+    open_url(&authorize_url);
+
+    let listener = TcpListener::bind("0.0.0.0:8080").await?;
+    let (mut stream, _) = listener.accept().await?;
+    let url = {
+        let mut reader = BufReader::new(&mut stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).await?;
+
+        let redirect_url = request_line
+            .split_whitespace()
+            .nth(1)
+            .ok_or_else(|| crate::Error::OAuth("malformed redirect request".to_owned()))?;
+        Url::parse(&("http://localhost".to_string() + redirect_url))
+            .map_err(|e| crate::Error::OAuth(e.to_string()))?
+    };
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    let message = "Go back to your terminal :)";
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+        message.len(),
+        message
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    if let Some(error) = query("error") {
+        return Err(crate::Error::OAuth(format!(
+            "authorization failed: {error}"
+        )));
+    }
+    let (code, state) = match (query("code"), query("state")) {
+        (Some(code), Some(state)) => (AuthorizationCode::new(code), CsrfToken::new(state)),
+        _ => {
+            return Err(crate::Error::OAuth(
+                "redirect carries no authorization code".to_owned(),
+            ))
         }
+    };
 
-        let message = "Go back to your terminal :)";
-        let response = format!(
-            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
-            message.len(),
-            message
-        );
-        stream.write_all(response.as_bytes()).await.unwrap();
-
-        // Verify that the state we generated matches the one the server sent us.
-        assert_eq!(
-            csrf_state.secret(),
-            state.secret(),
-            "CSRF state mismatch. Malicious actor?"
-        );
-
-        // Exchange the code with a token.
-        let token = match client
-            .exchange_code(code)
-            .request_async(async_http_client)
-            .await
-        {
-            Ok(t) => t,
-            Err(e) => {
-                eprintln!("OAuth2: {}", e);
-                eprintln!("Failed to exchange the code for a valid access_token.\nIncorrect client secret?");
-                std::process::exit(1);
-            }
-        };
-
-        return token;
+    // Verify that the state we generated matches the one the server sent us.
+    if csrf_state.secret() != state.secret() {
+        return Err(crate::Error::OAuth(
+            "CSRF state mismatch. Malicious actor?".to_owned(),
+        ));
     }
 
-    unreachable!();
+    // Exchange the code with a token.
+    client
+        .exchange_code(code)
+        .request_async(async_http_client)
+        .await
+        .map_err(|e| crate::Error::OAuth(e.to_string()))
 }
 
 fn write_auth_token(token: &Token, path: &Path) -> Result<()> {
-    std::fs::create_dir_all(path.parent().unwrap())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
     let file = std::fs::File::create(path)?;
     serde_json::to_writer_pretty(file, &token)?;
     Ok(())
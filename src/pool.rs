@@ -0,0 +1,205 @@
+//! Manage clients for many users at once.
+//!
+//! Each [`Client`] only holds a single user's token. A [`ClientPool`] holds
+//! one client per Fitbit user ID, and runs the same query for many of them
+//! with bounded concurrency.
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::PathBuf,
+};
+
+use futures_util::stream::{self, StreamExt};
+
+use crate::{error::Error, Client, Result};
+
+/// Number of users queried at the same time unless configured otherwise.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Clients for many users, keyed by Fitbit user ID.
+pub struct ClientPool {
+    client_id: String,
+    client_secret: String,
+    clients: HashMap<String, Client>,
+    concurrency: usize,
+}
+
+impl ClientPool {
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            clients: HashMap::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Maximum number of users queried at the same time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Add a user whose token was previously cached at `path`, e.g. by
+    /// [`Client::auth_interactive`].
+    pub fn add_user<P>(&mut self, user_id: &str, path: P) -> Result<()>
+    where
+        P: Into<PathBuf>,
+    {
+        let client = Client::new(&self.client_id, &self.client_secret)
+            .with_cache(path)
+            .auth_from_cache()?;
+        self.insert(user_id, client);
+        Ok(())
+    }
+
+    /// Add an already authenticated client for a user, replacing any
+    /// previous one.
+    pub fn insert(&mut self, user_id: &str, client: Client) {
+        self.clients.insert(user_id.to_owned(), client);
+    }
+
+    pub fn remove(&mut self, user_id: &str) -> Option<Client> {
+        self.clients.remove(user_id)
+    }
+
+    pub fn get(&self, user_id: &str) -> Option<&Client> {
+        self.clients.get(user_id)
+    }
+
+    pub fn user_ids(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    /// Run `query` for every user, at most
+    /// [`concurrency`](ClientPool::with_concurrency) at a time.
+    ///
+    /// `query` is passed the user's client and ID. Users who have used up
+    /// their rate limit are skipped with [`Error::RateLimited`] rather than
+    /// queried.
+    pub async fn for_each_user<'a, T, F, Fut>(&'a self, query: F) -> HashMap<String, Result<T>>
+    where
+        F: Fn(&'a Client, &'a str) -> Fut,
+        Fut: Future<Output = Result<T>> + 'a,
+    {
+        self.for_users(self.user_ids(), query).await
+    }
+
+    /// Like [`for_each_user`](ClientPool::for_each_user), but only for
+    /// `user_ids`. Unknown users result in [`Error::UnknownUser`]. Users
+    /// listed more than once are still only queried once.
+    pub async fn for_users<'a, I, T, F, Fut>(
+        &'a self,
+        user_ids: I,
+        query: F,
+    ) -> HashMap<String, Result<T>>
+    where
+        I: IntoIterator<Item = &'a str>,
+        F: Fn(&'a Client, &'a str) -> Fut,
+        Fut: Future<Output = Result<T>> + 'a,
+    {
+        let query = &query;
+        let user_ids: HashSet<_> = user_ids.into_iter().collect();
+        stream::iter(user_ids)
+            .map(|user_id| async move {
+                let result = match self.clients.get(user_id) {
+                    None => Err(Error::UnknownUser(user_id.to_owned())),
+                    Some(client) => match client.rate_limit().and_then(|r| r.exhausted_for()) {
+                        Some(retry_after) => Err(Error::RateLimited(retry_after)),
+                        None => query(client, user_id).await,
+                    },
+                };
+                (user_id.to_owned(), result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use futures_util::FutureExt;
+
+    use super::*;
+    use crate::middleware::{LocalBoxFuture, Middleware, Next};
+
+    /// Answers with the user's name and `remaining` requests left, counting
+    /// the requests it got.
+    struct Canned {
+        name: &'static str,
+        remaining: u32,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl Middleware for Canned {
+        fn handle<'a>(
+            &'a self,
+            _request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let response = http::Response::builder()
+                .header("fitbit-rate-limit-limit", "150")
+                .header("fitbit-rate-limit-remaining", self.remaining)
+                .header("fitbit-rate-limit-reset", "60")
+                .body(format!(r#"{{"name":"{}"}}"#, self.name))
+                .unwrap();
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    fn canned_client(name: &'static str, remaining: u32) -> (Client, Arc<AtomicU32>) {
+        let calls = Arc::default();
+        let client = Client::new("id", "secret").with_middleware(Canned {
+            name,
+            remaining,
+            calls: Arc::clone(&calls),
+        });
+        (client, calls)
+    }
+
+    async fn name(client: &Client, _user_id: &str) -> Result<String> {
+        let value: serde_json::Value = client.get("/1/user/-/profile.json", None::<&()>).await?;
+        Ok(value["name"].as_str().unwrap_or_default().to_owned())
+    }
+
+    #[tokio::test]
+    async fn skips_rate_limited_users() {
+        let mut pool = ClientPool::new("id", "secret");
+        let (a, a_calls) = canned_client("A", 149);
+        let (b, b_calls) = canned_client("B", 0);
+        pool.insert("A", a);
+        pool.insert("B", b);
+        // Uses up B's rate limit.
+        name(pool.get("B").unwrap(), "B").await.unwrap();
+
+        let results = pool.for_each_user(name).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["A"].as_ref().unwrap(), "A");
+        assert!(matches!(results["B"], Err(Error::RateLimited(wait)) if !wait.is_zero()));
+        assert_eq!(a_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(b_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn for_users() {
+        let mut pool = ClientPool::new("id", "secret");
+        let (a, a_calls) = canned_client("A", 149);
+        let (b, _) = canned_client("B", 149);
+        pool.insert("A", a);
+        pool.insert("B", b);
+
+        let results = pool.for_users(["A", "C", "A"], name).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["A"].as_ref().unwrap(), "A");
+        assert!(matches!(&results["C"], Err(Error::UnknownUser(user)) if user == "C"));
+        assert_eq!(a_calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,95 @@
+//! Fitbit's per-user rate limit, as reported in response headers.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use reqwest::header::HeaderMap;
+
+use crate::{error::Error, Client, Result};
+
+/// How long to wait after being rate limited without knowing when the
+/// limit resets.
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// The rate limit state of a user after their most recent request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed per window.
+    pub limit: u32,
+    /// Requests left in the current window.
+    pub remaining: u32,
+    /// When the current window ends and `remaining` resets to `limit`.
+    pub reset_at: Instant,
+}
+
+impl RateLimit {
+    /// Parse the `fitbit-rate-limit-*` headers of a response.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+
+        Some(Self {
+            limit: header("fitbit-rate-limit-limit")?.try_into().ok()?,
+            remaining: header("fitbit-rate-limit-remaining")?.try_into().ok()?,
+            reset_at: Instant::now() + Duration::from_secs(header("fitbit-rate-limit-reset")?),
+        })
+    }
+
+    /// Time left until the window resets, if no requests are left in the
+    /// current one.
+    pub fn exhausted_for(&self) -> Option<Duration> {
+        if self.remaining > 0 {
+            return None;
+        }
+        let now = Instant::now();
+        (self.reset_at > now).then(|| self.reset_at - now)
+    }
+}
+
+/// Call `request`, waiting for `client`'s rate limit to reset first if
+/// needed, and again whenever the request is rate limited.
+pub(crate) async fn throttled<T, F, Fut>(client: &Client, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    loop {
+        if let Some(wait) = client.rate_limit().and_then(|r| r.exhausted_for()) {
+            tokio::time::sleep(wait).await;
+        }
+        match request().await {
+            Err(Error::RateLimited(wait)) => {
+                let wait = if wait.is_zero() {
+                    DEFAULT_RATE_LIMIT_WAIT
+                } else {
+                    wait
+                };
+                tokio::time::sleep(wait).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("fitbit-rate-limit-limit", "150".parse().unwrap());
+        headers.insert("fitbit-rate-limit-remaining", "0".parse().unwrap());
+        headers.insert("fitbit-rate-limit-reset", "1800".parse().unwrap());
+
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(rate_limit.limit, 150);
+        assert!(rate_limit.exhausted_for().unwrap() > Duration::from_secs(1700));
+    }
+
+    #[test]
+    fn missing_headers() {
+        assert!(RateLimit::from_headers(&HeaderMap::new()).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//! Keep the raw JSON of a response alongside its typed value.
+
+use std::ops::Deref;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+/// A deserialized value along with the JSON it was deserialized from.
+///
+/// Useful for getting at fields the typed model doesn't cover (yet).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithRaw<T> {
+    pub value: T,
+    pub raw: serde_json::Value,
+}
+
+impl<T> WithRaw<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for WithRaw<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for WithRaw<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        let value = T::deserialize(&raw).map_err(serde::de::Error::custom)?;
+        Ok(Self { value, raw })
+    }
+}
+
+/// Serializes the raw JSON, so nothing is lost when re-emitting it.
+impl<T> Serialize for WithRaw<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::body::fat::FatLog;
+
+    #[test]
+    fn deserialize() {
+        let data = r#"
+{
+    "date":"2012-03-05",
+    "fat":13.5,
+    "logId":1330991999000,
+    "time":"21:20:59",
+    "source":"Aria",
+    "newField":true
+}
+        "#;
+
+        let res: WithRaw<FatLog> = serde_json::from_str(data).unwrap();
+        assert_eq!(res.fat, 13.5);
+        assert_eq!(res.raw["newField"], true);
+        assert_eq!(serde_json::to_value(&res).unwrap(), res.raw);
+    }
+}
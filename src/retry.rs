@@ -0,0 +1,228 @@
+//! Retrying transient failures.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use futures_util::FutureExt;
+use reqwest::{Method, StatusCode};
+
+use crate::{
+    api_error,
+    middleware::{LocalBoxFuture, Middleware, Next},
+    Error, Result,
+};
+
+/// When and how often a failed request is retried.
+///
+/// By default, `GET`, `PUT` and `DELETE` requests are attempted up to 3
+/// times when the connection fails, times out, or Fitbit answers with a
+/// 500, 502, 503 or 504. Waits between attempts grow exponentially, with
+/// jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    statuses: Vec<StatusCode>,
+    retry_error: fn(&reqwest::Error) -> bool,
+    non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            statuses: vec![
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_error: is_transient,
+            non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Attempts per request, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Wait `initial` before the first retry, doubling for every further
+    /// retry up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Whether to wait a random part of the backoff, so many clients don't
+    /// retry in lockstep.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Response statuses to retry.
+    pub fn with_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Which errors from sending a request to retry. Defaults to connection
+    /// failures and timeouts.
+    pub fn with_retry_error(mut self, retry_error: fn(&reqwest::Error) -> bool) -> Self {
+        self.retry_error = retry_error;
+        self
+    }
+
+    /// Also retry `POST` and `PATCH` requests. Fitbit doesn't deduplicate
+    /// those, so a retry may log things twice.
+    pub fn with_non_idempotent(mut self, non_idempotent: bool) -> Self {
+        self.non_idempotent = non_idempotent;
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether requests with `method` may be retried at all.
+    pub(crate) fn allows(&self, method: &Method) -> bool {
+        self.non_idempotent || is_idempotent(method)
+    }
+
+    /// Whether the outcome of an attempt is worth retrying.
+    pub(crate) fn should_retry(&self, outcome: &Result<reqwest::Response>) -> bool {
+        match outcome {
+            Ok(response) => self.statuses.contains(&response.status()),
+            Err(Error::Reqwest(e)) => (self.retry_error)(e),
+            Err(_) => false,
+        }
+    }
+
+    /// How long to wait after `attempt` failed.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(random_fraction())
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Middleware for RetryPolicy {
+    fn handle<'a>(
+        &'a self,
+        mut request: reqwest::Request,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+        async move {
+            if !self.allows(request.method()) {
+                return next.run(request).await;
+            }
+
+            let mut attempt = 1;
+            let outcome = loop {
+                // Requests with streaming bodies can't be copied, and so are
+                // only sent once.
+                let retry = request.try_clone();
+                let outcome = next.run(request).await;
+                match retry {
+                    Some(retry) if attempt < self.max_attempts && self.should_retry(&outcome) => {
+                        let backoff = self.backoff(attempt);
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(attempt, ?backoff, "retrying request");
+                        tokio::time::sleep(backoff).await;
+                        attempt += 1;
+                        request = retry;
+                    }
+                    _ => break outcome,
+                }
+            };
+            if attempt == 1 {
+                return outcome;
+            }
+
+            let error = match outcome {
+                // Not wrapped in `Error::Retried`, so callers can always match
+                // on `Error::RateLimited` to wait for the reset.
+                Ok(response)
+                    if response.status().is_success()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    return Ok(response)
+                }
+                Ok(response) => api_error(response).await,
+                Err(e) => e,
+            };
+            Err(Error::Retried {
+                attempts: attempt,
+                source: Box::new(error),
+            })
+        }
+        .boxed_local()
+    }
+}
+
+fn is_transient(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout() || e.is_request()
+}
+
+/// A random number in `[0, 1)`, good enough for jitter.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    !matches!(*method, Method::POST | Method::PATCH | Method::CONNECT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .with_jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+
+        let policy = policy.with_jitter(true);
+        assert!((1..10).all(|attempt| policy.backoff(attempt) <= Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        let policy = RetryPolicy::default();
+        assert!(policy.allows(&Method::GET));
+        assert!(policy.allows(&Method::DELETE));
+        assert!(!policy.allows(&Method::POST));
+        assert!(policy.with_non_idempotent(true).allows(&Method::POST));
+    }
+}
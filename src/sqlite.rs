@@ -0,0 +1,858 @@
+//! Store a user's data in a SQLite database.
+//!
+//! A [`Database`] creates a table per kind of data, keyed by user and by
+//! Fitbit's log ID or the date, so storing a record again replaces it. Its
+//! upsert functions take the same models the API returns and its queries
+//! return them again, while the tables stay plain enough to query with SQL
+//! directly:
+//!
+//! ```no_run
+//! # async fn run(client: &fitbit_rs::Client) -> fitbit_rs::Result<()> {
+//! use fitbit_rs::{sqlite::Database, sync::Syncer};
+//!
+//! let mut syncer = Syncer::new(client, Database::open("fitbit.db")?);
+//! // Stores the records of each resource in the same transaction as its
+//! // checkpoint.
+//! syncer.sync_all("-").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Nested values without a column of their own, such as heart rate zones
+//! and fields the models don't know about, are stored as JSON text.
+
+use std::path::Path;
+
+use chrono::NaiveDate;
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    models::{
+        activity::summary::Summary as ActivitySummary,
+        body::{fat::FatLog, weight::WeightLog},
+        devices::DeviceInfo,
+        heart_rate::{
+            intraday_time_series::{Intraday, IntradayEntry},
+            time_series::{DayEntry, Value},
+            HeartRateZone,
+        },
+        sleep::list::{Levels, Sleep, SleepDataPoint},
+    },
+    sync::{CheckpointStore, Record, Resource},
+    DateRange, Result,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS weight (
+    user_id TEXT NOT NULL,
+    log_id INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    weight REAL NOT NULL,
+    bmi REAL NOT NULL,
+    source TEXT,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, log_id)
+);
+CREATE INDEX IF NOT EXISTS weight_date ON weight (user_id, date);
+
+CREATE TABLE IF NOT EXISTS fat (
+    user_id TEXT NOT NULL,
+    log_id INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    fat REAL NOT NULL,
+    source TEXT NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, log_id)
+);
+CREATE INDEX IF NOT EXISTS fat_date ON fat (user_id, date);
+
+CREATE TABLE IF NOT EXISTS sleep (
+    user_id TEXT NOT NULL,
+    log_id INTEGER NOT NULL,
+    date_of_sleep TEXT NOT NULL,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    duration INTEGER NOT NULL,
+    efficiency INTEGER NOT NULL,
+    info_code INTEGER NOT NULL,
+    is_main_sleep INTEGER NOT NULL,
+    minutes_after_wakeup INTEGER NOT NULL,
+    minutes_asleep INTEGER NOT NULL,
+    minutes_awake INTEGER NOT NULL,
+    minutes_to_fall_asleep INTEGER NOT NULL,
+    time_in_bed INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    level_summary TEXT NOT NULL,
+    levels_extra TEXT NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, log_id)
+);
+CREATE INDEX IF NOT EXISTS sleep_date ON sleep (user_id, date_of_sleep);
+
+CREATE TABLE IF NOT EXISTS sleep_stages (
+    user_id TEXT NOT NULL,
+    log_id INTEGER NOT NULL,
+    short INTEGER NOT NULL,
+    date_time TEXT NOT NULL,
+    level TEXT NOT NULL,
+    seconds INTEGER NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, log_id, short, date_time)
+);
+
+CREATE TABLE IF NOT EXISTS activity_summaries (
+    user_id TEXT NOT NULL,
+    date TEXT NOT NULL,
+    steps INTEGER NOT NULL,
+    distance REAL,
+    floors INTEGER NOT NULL,
+    elevation REAL NOT NULL,
+    calories_out INTEGER NOT NULL,
+    activity_calories INTEGER NOT NULL,
+    calories_bmr INTEGER NOT NULL,
+    marginal_calories INTEGER NOT NULL,
+    sedentary_minutes INTEGER NOT NULL,
+    lightly_active_minutes INTEGER NOT NULL,
+    fairly_active_minutes INTEGER NOT NULL,
+    very_active_minutes INTEGER NOT NULL,
+    distances TEXT NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, date)
+);
+
+CREATE TABLE IF NOT EXISTS heart_rate_daily (
+    user_id TEXT NOT NULL,
+    date TEXT NOT NULL,
+    resting_heart_rate INTEGER,
+    heart_rate_zones TEXT NOT NULL,
+    custom_heart_rate_zones TEXT NOT NULL,
+    value_extra TEXT NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, date)
+);
+
+CREATE TABLE IF NOT EXISTS heart_rate_intraday (
+    user_id TEXT NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    bpm INTEGER NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, date, time)
+);
+
+CREATE TABLE IF NOT EXISTS devices (
+    user_id TEXT NOT NULL,
+    id TEXT NOT NULL,
+    device_version TEXT NOT NULL,
+    type TEXT NOT NULL,
+    battery TEXT NOT NULL,
+    battery_level INTEGER NOT NULL,
+    last_sync_time TEXT NOT NULL,
+    extra TEXT NOT NULL,
+    PRIMARY KEY (user_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS sync_checkpoints (
+    user_id TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    date TEXT NOT NULL,
+    PRIMARY KEY (user_id, resource)
+);
+";
+
+/// A SQLite database of one or more users' data.
+#[derive(Debug)]
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Open the database at `path`, creating it and any missing tables.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// A database which only lives as long as the returned value.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// The underlying connection, to run any other SQL.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Store a record returned by a [`Syncer`](crate::sync::Syncer).
+    pub fn upsert_record(&mut self, user_id: &str, record: &Record) -> Result<()> {
+        match record {
+            Record::Weight(log) => self.upsert_weight_logs(user_id, std::slice::from_ref(log)),
+            Record::Sleep(sleep) => self.upsert_sleep(user_id, std::slice::from_ref(&**sleep)),
+            Record::HeartRate(entry) => {
+                self.upsert_heart_rate_summaries(user_id, std::slice::from_ref(entry))
+            }
+            Record::Activity { date, summary } => {
+                self.upsert_activity_summary(user_id, *date, &summary.summary)
+            }
+        }
+    }
+
+    pub fn upsert_weight_logs(&mut self, user_id: &str, logs: &[WeightLog]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO weight
+                 (user_id, log_id, date, time, weight, bmi, source, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for log in logs {
+                insert.execute(params![
+                    user_id,
+                    log.log_id,
+                    log.date,
+                    log.time,
+                    log.weight,
+                    log.bmi,
+                    log.source,
+                    to_json(&log.extra)?,
+                ])?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Weight logs within `range`, oldest first.
+    pub fn weight_logs(&self, user_id: &str, range: DateRange) -> Result<Vec<WeightLog>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT log_id, date, time, weight, bmi, source, extra FROM weight
+             WHERE user_id = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY date, time",
+        )?;
+        let logs = select
+            .query_map(params![user_id, range.start(), range.end()], |row| {
+                Ok(WeightLog {
+                    log_id: row.get(0)?,
+                    date: row.get(1)?,
+                    time: row.get(2)?,
+                    weight: row.get(3)?,
+                    bmi: row.get(4)?,
+                    source: row.get(5)?,
+                    extra: from_json(row, 6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(logs)
+    }
+
+    pub fn upsert_fat_logs(&mut self, user_id: &str, logs: &[FatLog]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO fat (user_id, log_id, date, time, fat, source, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for log in logs {
+                insert.execute(params![
+                    user_id,
+                    log.log_id,
+                    log.date,
+                    log.time,
+                    log.fat,
+                    log.source,
+                    to_json(&log.extra)?,
+                ])?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Body fat logs within `range`, oldest first.
+    pub fn fat_logs(&self, user_id: &str, range: DateRange) -> Result<Vec<FatLog>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT log_id, date, time, fat, source, extra FROM fat
+             WHERE user_id = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY date, time",
+        )?;
+        let logs = select
+            .query_map(params![user_id, range.start(), range.end()], |row| {
+                Ok(FatLog {
+                    log_id: row.get(0)?,
+                    date: row.get(1)?,
+                    time: row.get(2)?,
+                    fat: row.get(3)?,
+                    source: row.get(4)?,
+                    extra: from_json(row, 5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(logs)
+    }
+
+    /// Store sleep sessions, replacing the stages of any already stored.
+    pub fn upsert_sleep(&mut self, user_id: &str, sleep: &[Sleep]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO sleep
+                 (user_id, log_id, date_of_sleep, start_time, end_time, duration, efficiency,
+                  info_code, is_main_sleep, minutes_after_wakeup, minutes_asleep, minutes_awake,
+                  minutes_to_fall_asleep, time_in_bed, type, level_summary, levels_extra, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                  ?17, ?18)",
+            )?;
+            let mut delete_stages =
+                tx.prepare_cached("DELETE FROM sleep_stages WHERE user_id = ?1 AND log_id = ?2")?;
+            let mut insert_stage = tx.prepare_cached(
+                "INSERT OR REPLACE INTO sleep_stages
+                 (user_id, log_id, short, date_time, level, seconds, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+
+            for s in sleep {
+                insert.execute(params![
+                    user_id,
+                    s.log_id,
+                    s.date_of_sleep,
+                    s.start_time,
+                    s.end_time,
+                    s.duration_millis,
+                    s.efficiency,
+                    s.info_code,
+                    s.is_main_sleep,
+                    s.minutes_after_wakeup,
+                    s.minutes_asleep,
+                    s.minutes_awake,
+                    s.minutes_to_fall_asleep,
+                    s.time_in_bed,
+                    s.type_,
+                    to_json(&s.levels.summary)?,
+                    to_json(&s.levels.extra)?,
+                    to_json(&s.extra)?,
+                ])?;
+
+                delete_stages.execute(params![user_id, s.log_id])?;
+                let stages = [(false, &s.levels.data), (true, &s.levels.short_data)];
+                for (short, points) in stages {
+                    for point in points {
+                        insert_stage.execute(params![
+                            user_id,
+                            s.log_id,
+                            short,
+                            point.date_time,
+                            to_text(&point.level)?,
+                            point.seconds,
+                            to_json(&point.extra)?,
+                        ])?;
+                    }
+                }
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Sleep sessions ending within `range`, with their stages, oldest
+    /// first.
+    pub fn sleep(&self, user_id: &str, range: DateRange) -> Result<Vec<Sleep>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT log_id, date_of_sleep, start_time, end_time, duration, efficiency, info_code,
+                    is_main_sleep, minutes_after_wakeup, minutes_asleep, minutes_awake,
+                    minutes_to_fall_asleep, time_in_bed, type, level_summary, levels_extra, extra
+             FROM sleep
+             WHERE user_id = ?1 AND date_of_sleep BETWEEN ?2 AND ?3 ORDER BY start_time",
+        )?;
+        let mut sleep = select
+            .query_map(params![user_id, range.start(), range.end()], |row| {
+                Ok(Sleep {
+                    log_id: row.get(0)?,
+                    date_of_sleep: row.get(1)?,
+                    start_time: row.get(2)?,
+                    end_time: row.get(3)?,
+                    duration_millis: row.get(4)?,
+                    efficiency: row.get(5)?,
+                    info_code: row.get(6)?,
+                    is_main_sleep: row.get(7)?,
+                    minutes_after_wakeup: row.get(8)?,
+                    minutes_asleep: row.get(9)?,
+                    minutes_awake: row.get(10)?,
+                    minutes_to_fall_asleep: row.get(11)?,
+                    time_in_bed: row.get(12)?,
+                    type_: row.get(13)?,
+                    levels: Levels {
+                        summary: from_json(row, 14)?,
+                        data: Vec::new(),
+                        short_data: Vec::new(),
+                        extra: from_json(row, 15)?,
+                    },
+                    extra: from_json(row, 16)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stages = self.conn.prepare_cached(
+            "SELECT short, date_time, level, seconds, extra FROM sleep_stages
+             WHERE user_id = ?1 AND log_id = ?2 ORDER BY date_time",
+        )?;
+        for s in &mut sleep {
+            let mut rows = stages.query(params![user_id, s.log_id])?;
+            while let Some(row) = rows.next()? {
+                let point = SleepDataPoint {
+                    date_time: row.get(1)?,
+                    level: from_text(row, 2)?,
+                    seconds: row.get(3)?,
+                    extra: from_json(row, 4)?,
+                };
+                if row.get(0)? {
+                    s.levels.short_data.push(point);
+                } else {
+                    s.levels.data.push(point);
+                }
+            }
+        }
+        Ok(sleep)
+    }
+
+    /// Store the activity summary of `date`. Its total distance gets a
+    /// column of its own.
+    pub fn upsert_activity_summary(
+        &mut self,
+        user_id: &str,
+        date: NaiveDate,
+        summary: &ActivitySummary,
+    ) -> Result<()> {
+        let distance = summary
+            .distances
+            .iter()
+            .find(|d| d.activity == "total")
+            .map(|d| d.distance);
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO activity_summaries
+                 (user_id, date, steps, distance, floors, elevation, calories_out,
+                  activity_calories, calories_bmr, marginal_calories, sedentary_minutes,
+                  lightly_active_minutes, fairly_active_minutes, very_active_minutes, distances,
+                  extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            )?
+            .execute(params![
+                user_id,
+                date,
+                summary.steps,
+                distance,
+                summary.floors,
+                summary.elevation,
+                summary.calories_out,
+                summary.activity_calories,
+                summary.calories_bmr,
+                summary.marginal_calories,
+                summary.sedentary_minutes,
+                summary.lightly_active_minutes,
+                summary.fairly_active_minutes,
+                summary.very_active_minutes,
+                to_json(&summary.distances)?,
+                to_json(&summary.extra)?,
+            ])?;
+        Ok(())
+    }
+
+    /// Daily activity summaries within `range`, oldest first.
+    pub fn activity_summaries(
+        &self,
+        user_id: &str,
+        range: DateRange,
+    ) -> Result<Vec<(NaiveDate, ActivitySummary)>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT date, steps, floors, elevation, calories_out, activity_calories,
+                    calories_bmr, marginal_calories, sedentary_minutes, lightly_active_minutes,
+                    fairly_active_minutes, very_active_minutes, distances, extra
+             FROM activity_summaries
+             WHERE user_id = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY date",
+        )?;
+        let summaries = select
+            .query_map(params![user_id, range.start(), range.end()], |row| {
+                let summary = ActivitySummary {
+                    steps: row.get(1)?,
+                    floors: row.get(2)?,
+                    elevation: row.get(3)?,
+                    calories_out: row.get(4)?,
+                    activity_calories: row.get(5)?,
+                    calories_bmr: row.get(6)?,
+                    marginal_calories: row.get(7)?,
+                    sedentary_minutes: row.get(8)?,
+                    lightly_active_minutes: row.get(9)?,
+                    fairly_active_minutes: row.get(10)?,
+                    very_active_minutes: row.get(11)?,
+                    distances: from_json(row, 12)?,
+                    extra: from_json(row, 13)?,
+                };
+                Ok((row.get(0)?, summary))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(summaries)
+    }
+
+    /// Store daily heart rate summaries.
+    pub fn upsert_heart_rate_summaries(&mut self, user_id: &str, days: &[DayEntry]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO heart_rate_daily
+                 (user_id, date, resting_heart_rate, heart_rate_zones, custom_heart_rate_zones,
+                  value_extra, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for day in days {
+                insert.execute(params![
+                    user_id,
+                    day.date_time,
+                    day.value.resting_heart_rate,
+                    to_json(&day.value.heart_rate_zones)?,
+                    to_json(&day.value.custom_heart_rate_zones)?,
+                    to_json(&day.value.extra)?,
+                    to_json(&day.extra)?,
+                ])?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Daily heart rate summaries within `range`, oldest first.
+    pub fn heart_rate_summaries(&self, user_id: &str, range: DateRange) -> Result<Vec<DayEntry>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT date, resting_heart_rate, heart_rate_zones, custom_heart_rate_zones,
+                    value_extra, extra
+             FROM heart_rate_daily
+             WHERE user_id = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY date",
+        )?;
+        let days = select
+            .query_map(params![user_id, range.start(), range.end()], |row| {
+                Ok(DayEntry {
+                    date_time: row.get(0)?,
+                    value: Value {
+                        resting_heart_rate: row.get(1)?,
+                        heart_rate_zones: from_json::<Vec<HeartRateZone>>(row, 2)?,
+                        custom_heart_rate_zones: from_json(row, 3)?,
+                        extra: from_json(row, 4)?,
+                    },
+                    extra: from_json(row, 5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(days)
+    }
+
+    /// Store the intraday heart rate of `date`, at whatever detail level it
+    /// was fetched, replacing any data points already stored for it.
+    pub fn upsert_heart_rate(
+        &mut self,
+        user_id: &str,
+        date: NaiveDate,
+        intraday: &Intraday,
+    ) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            // Points from a finer detail level than the new one would
+            // otherwise be left in between.
+            tx.prepare_cached("DELETE FROM heart_rate_intraday WHERE user_id = ?1 AND date = ?2")?
+                .execute(params![user_id, date])?;
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO heart_rate_intraday (user_id, date, time, bpm, extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for entry in &intraday.dataset {
+                insert.execute(params![
+                    user_id,
+                    date,
+                    entry.time,
+                    entry.value,
+                    to_json(&entry.extra)?,
+                ])?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// The intraday heart rate data points stored for `date`, in order.
+    pub fn heart_rate(&self, user_id: &str, date: NaiveDate) -> Result<Vec<IntradayEntry>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT time, bpm, extra FROM heart_rate_intraday
+             WHERE user_id = ?1 AND date = ?2 ORDER BY time",
+        )?;
+        let entries = select
+            .query_map(params![user_id, date], |row| {
+                Ok(IntradayEntry {
+                    time: row.get(0)?,
+                    value: row.get(1)?,
+                    extra: from_json(row, 2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+
+    pub fn upsert_devices(&mut self, user_id: &str, devices: &[DeviceInfo]) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO devices
+                 (user_id, id, device_version, type, battery, battery_level, last_sync_time,
+                  extra)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for device in devices {
+                insert.execute(params![
+                    user_id,
+                    device.id,
+                    to_text(&device.device_version)?,
+                    to_text(&device.ty)?,
+                    to_text(&device.battery)?,
+                    device.battery_percentage,
+                    device.last_sync_time,
+                    to_json(&device.extra)?,
+                ])?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Every device stored for the user, as of its last upsert.
+    pub fn devices(&self, user_id: &str) -> Result<Vec<DeviceInfo>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT id, device_version, type, battery, battery_level, last_sync_time, extra
+             FROM devices WHERE user_id = ?1 ORDER BY id",
+        )?;
+        let devices = select
+            .query_map(params![user_id], |row| {
+                Ok(DeviceInfo {
+                    id: row.get(0)?,
+                    device_version: from_text(row, 1)?,
+                    ty: from_text(row, 2)?,
+                    battery: from_text(row, 3)?,
+                    battery_percentage: row.get(4)?,
+                    last_sync_time: row.get(5)?,
+                    extra: from_json(row, 6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(devices)
+    }
+}
+
+/// Keeps sync checkpoints next to the synced data.
+impl CheckpointStore for Database {
+    fn get(&self, user_id: &str, resource: Resource) -> Result<Option<NaiveDate>> {
+        Ok(self
+            .conn
+            .prepare_cached(
+                "SELECT date FROM sync_checkpoints WHERE user_id = ?1 AND resource = ?2",
+            )?
+            .query_row(params![user_id, resource.as_str()], |row| row.get(0))
+            .optional()?)
+    }
+
+    fn set(&mut self, user_id: &str, resource: Resource, date: NaiveDate) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO sync_checkpoints (user_id, resource, date)
+                 VALUES (?1, ?2, ?3)",
+            )?
+            .execute(params![user_id, resource.as_str(), date])?;
+        Ok(())
+    }
+
+    /// Upsert `records` and set the checkpoint atomically.
+    fn commit(
+        &mut self,
+        user_id: &str,
+        resource: Resource,
+        date: NaiveDate,
+        records: &[Record],
+    ) -> Result<()> {
+        // The upserts use savepoints of their own, which nest inside this
+        // one.
+        self.conn.execute_batch("SAVEPOINT sync_commit")?;
+        let result = records
+            .iter()
+            .try_for_each(|record| self.upsert_record(user_id, record))
+            .and_then(|()| self.set(user_id, resource, date));
+        if result.is_err() {
+            self.conn.execute_batch("ROLLBACK TO sync_commit")?;
+        }
+        self.conn.execute_batch("RELEASE sync_commit")?;
+        result
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+/// A unit enum variant as the string Fitbit uses for it.
+fn to_text<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => Ok(s),
+        value => Ok(value.to_string()),
+    }
+}
+
+fn from_json<T: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(idx)?;
+    serde_json::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+}
+
+fn from_text<T: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(idx)?;
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Extra, synthetic::User};
+
+    fn range() -> DateRange {
+        let end = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+        DateRange::ending_at(end, 14)
+    }
+
+    #[test]
+    fn round_trip() {
+        let user = User::new(5);
+        let mut db = Database::open_in_memory().unwrap();
+
+        let weights = user.weight_logs(range());
+        db.upsert_weight_logs("A", &weights).unwrap();
+        // Upserting again replaces rather than duplicates.
+        db.upsert_weight_logs("A", &weights).unwrap();
+        assert_eq!(db.weight_logs("A", range()).unwrap(), weights);
+        assert!(db.weight_logs("B", range()).unwrap().is_empty());
+
+        let fats = user.fat_logs(range());
+        db.upsert_fat_logs("A", &fats).unwrap();
+        assert_eq!(db.fat_logs("A", range()).unwrap(), fats);
+
+        let mut sleep = user.sleeps(range());
+        sleep[0].levels.short_data = sleep[0].levels.data[..1].to_vec();
+        sleep[0]
+            .extra
+            .insert("logType".into(), "auto_detected".into());
+        sleep[0].levels.data[0]
+            .extra
+            .insert("confidence".into(), 0.9.into());
+        db.upsert_sleep("A", &sleep).unwrap();
+        db.upsert_sleep("A", &sleep).unwrap();
+        assert_eq!(db.sleep("A", range()).unwrap(), sleep);
+
+        let date = range().end();
+        let mut intraday = user.heart_rate(date);
+        intraday.dataset[0]
+            .extra
+            .insert("confidence".into(), 2.into());
+        let mut finer = intraday.clone();
+        finer.dataset[0].time += chrono::TimeDelta::seconds(1);
+        db.upsert_heart_rate("A", date, &finer).unwrap();
+        db.upsert_heart_rate("A", date, &intraday).unwrap();
+        assert_eq!(db.heart_rate("A", date).unwrap(), intraday.dataset);
+
+        let summaries: Vec<_> = range()
+            .dates()
+            .map(|date| {
+                let summary = user.heart_rate_summary(date);
+                DayEntry {
+                    date_time: date,
+                    value: Value {
+                        custom_heart_rate_zones: summary.value.custom_heart_rate_zones,
+                        heart_rate_zones: summary.value.heart_rate_zones,
+                        resting_heart_rate: summary.value.value,
+                        extra: Extra::default(),
+                    },
+                    extra: Extra::default(),
+                }
+            })
+            .collect();
+        db.upsert_heart_rate_summaries("A", &summaries).unwrap();
+        assert_eq!(db.heart_rate_summaries("A", range()).unwrap(), summaries);
+
+        let mut devices = user.devices(date.and_hms_opt(12, 0, 0).unwrap());
+        db.upsert_devices("A", &devices).unwrap();
+        devices.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(db.devices("A").unwrap(), devices);
+    }
+
+    #[test]
+    fn activity_summary() {
+        let data = r#"
+{
+    "activityCalories":230,
+    "caloriesBMR":1913,
+    "caloriesOut":2143,
+    "distances":[
+        {"activity":"tracker", "distance":1.32},
+        {"activity":"total","distance":1.32}
+    ],
+    "elevation":48.77,
+    "fairlyActiveMinutes":0,
+    "floors":16,
+    "lightlyActiveMinutes":0,
+    "marginalCalories":200,
+    "sedentaryMinutes":1166,
+    "steps":0,
+    "veryActiveMinutes":0
+}
+        "#;
+        let summary: ActivitySummary = serde_json::from_str(data).unwrap();
+        let mut db = Database::open_in_memory().unwrap();
+        db.upsert_activity_summary("A", range().end(), &summary)
+            .unwrap();
+
+        assert_eq!(
+            db.activity_summaries("A", range()).unwrap(),
+            [(range().end(), summary)]
+        );
+        let distance: f32 = db
+            .connection()
+            .query_row("SELECT distance FROM activity_summaries", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(distance, 1.32);
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut db = Database::open_in_memory().unwrap();
+        assert_eq!(db.get("A", Resource::Sleep).unwrap(), None);
+        db.set("A", Resource::Sleep, range().end()).unwrap();
+        assert_eq!(db.get("A", Resource::Sleep).unwrap(), Some(range().end()));
+        assert_eq!(db.get("A", Resource::Weight).unwrap(), None);
+    }
+
+    #[test]
+    fn commit() {
+        let weights = User::new(5).weight_logs(range());
+        let records: Vec<_> = weights.iter().cloned().map(Record::Weight).collect();
+        let mut db = Database::open_in_memory().unwrap();
+        db.commit("A", Resource::Weight, range().end(), &records)
+            .unwrap();
+        assert_eq!(db.weight_logs("A", range()).unwrap(), weights);
+        assert_eq!(db.get("A", Resource::Weight).unwrap(), Some(range().end()));
+
+        // Failing to set the checkpoint also undoes the upserts.
+        db.connection()
+            .execute_batch("DROP TABLE sync_checkpoints")
+            .unwrap();
+        assert!(db
+            .commit("B", Resource::Weight, range().end(), &records)
+            .is_err());
+        assert!(db.weight_logs("B", range()).unwrap().is_empty());
+    }
+}
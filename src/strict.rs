@@ -0,0 +1,44 @@
+//! Opt-in strict deserialization.
+//!
+//! By default models tolerate data they don't know about, see
+//! [`models::Extra`](crate::models::Extra). Strict mode instead fails on any
+//! unknown field or enum value, which is handy in tests to find out what the
+//! models are missing.
+
+use std::cell::Cell;
+
+use serde::de::DeserializeOwned;
+
+use crate::Result;
+
+thread_local! {
+    static STRICT: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(crate) fn is_enabled() -> bool {
+    STRICT.with(Cell::get)
+}
+
+/// Run `f` with strict mode set to `enabled`, restoring the previous mode
+/// afterwards.
+pub(crate) fn with<T>(enabled: bool, f: impl FnOnce() -> T) -> T {
+    struct Reset(bool);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            STRICT.with(|strict| strict.set(self.0));
+        }
+    }
+
+    let _reset = Reset(STRICT.with(|strict| strict.replace(enabled)));
+    f()
+}
+
+/// Deserialize `json`, failing on unknown fields or enum values.
+pub fn from_str<T: DeserializeOwned>(json: &str) -> Result<T> {
+    Ok(with(true, || serde_json::from_str(json))?)
+}
+
+/// Deserialize `value`, failing on unknown fields or enum values.
+pub fn from_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T> {
+    Ok(with(true, || serde_json::from_value(value))?)
+}
@@ -0,0 +1,399 @@
+//! Incrementally sync a user's data.
+//!
+//! A [`Syncer`] remembers, per user and [`Resource`], the last day it synced
+//! in a [`CheckpointStore`]. Each run only fetches the days since then, plus
+//! a trailing window of already synced days: devices often sync late, so the
+//! most recent days can still change after they were first fetched.
+//!
+//! Records in the trailing window are emitted again on every run. Use
+//! [`Record::key`] to upsert them.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+};
+
+use chrono::{Duration, NaiveDate, Utc};
+
+use crate::{
+    models::{
+        activity::summary, body::weight::WeightLog, heart_rate::time_series::DayEntry,
+        sleep::list::Sleep,
+    },
+    Client, DateRange, Result,
+};
+
+/// Days re-fetched before the last checkpoint unless configured otherwise.
+const DEFAULT_TRAILING_DAYS: u32 = 3;
+/// Days fetched on the first sync unless configured otherwise.
+const DEFAULT_INITIAL_DAYS: u32 = 30;
+
+/// A kind of data which can be synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Resource {
+    Weight,
+    Sleep,
+    HeartRate,
+    Activity,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 4] = [
+        Resource::Weight,
+        Resource::Sleep,
+        Resource::HeartRate,
+        Resource::Activity,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resource::Weight => "weight",
+            Resource::Sleep => "sleep",
+            Resource::HeartRate => "heart_rate",
+            Resource::Activity => "activity",
+        }
+    }
+}
+
+/// A synced record.
+#[derive(Debug)]
+pub enum Record {
+    Weight(WeightLog),
+    Sleep(Box<Sleep>),
+    HeartRate(DayEntry),
+    Activity {
+        date: NaiveDate,
+        summary: summary::Response,
+    },
+}
+
+/// Uniquely identifies a [`Record`] of a given [`Resource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordKey {
+    /// Records which Fitbit assigns a log ID.
+    LogId(u64),
+    /// Daily records.
+    Date(NaiveDate),
+}
+
+impl Record {
+    pub fn resource(&self) -> Resource {
+        match self {
+            Record::Weight(_) => Resource::Weight,
+            Record::Sleep(_) => Resource::Sleep,
+            Record::HeartRate(_) => Resource::HeartRate,
+            Record::Activity { .. } => Resource::Activity,
+        }
+    }
+
+    pub fn key(&self) -> RecordKey {
+        match self {
+            Record::Weight(log) => RecordKey::LogId(log.log_id),
+            Record::Sleep(sleep) => RecordKey::LogId(sleep.log_id),
+            Record::HeartRate(entry) => RecordKey::Date(entry.date_time),
+            Record::Activity { date, .. } => RecordKey::Date(*date),
+        }
+    }
+
+    /// The day the record belongs to.
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Record::Weight(log) => log.date,
+            Record::Sleep(sleep) => sleep.date_of_sleep,
+            Record::HeartRate(entry) => entry.date_time,
+            Record::Activity { date, .. } => *date,
+        }
+    }
+}
+
+/// Persists the last synced day per user and resource.
+pub trait CheckpointStore {
+    fn get(&self, user_id: &str, resource: Resource) -> Result<Option<NaiveDate>>;
+    fn set(&mut self, user_id: &str, resource: Resource, date: NaiveDate) -> Result<()>;
+
+    /// Called by [`Syncer::sync`] with the `records` synced up to `date`.
+    /// Stores which also keep the records should save them together with the
+    /// checkpoint, so that a crash in between can't skip them on the next
+    /// run. By default only the checkpoint is set.
+    fn commit(
+        &mut self,
+        user_id: &str,
+        resource: Resource,
+        date: NaiveDate,
+        _records: &[Record],
+    ) -> Result<()> {
+        self.set(user_id, resource, date)
+    }
+}
+
+/// Keeps checkpoints in memory only.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    checkpoints: HashMap<(String, Resource), NaiveDate>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CheckpointStore for MemoryStore {
+    fn get(&self, user_id: &str, resource: Resource) -> Result<Option<NaiveDate>> {
+        Ok(self
+            .checkpoints
+            .get(&(user_id.to_owned(), resource))
+            .copied())
+    }
+
+    fn set(&mut self, user_id: &str, resource: Resource, date: NaiveDate) -> Result<()> {
+        self.checkpoints
+            .insert((user_id.to_owned(), resource), date);
+        Ok(())
+    }
+}
+
+/// Keeps checkpoints in a JSON file, rewritten on every update.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    checkpoints: BTreeMap<String, NaiveDate>,
+}
+
+impl FileStore {
+    /// Open the store at `path`. The file is created on the first update if
+    /// it doesn't exist yet.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let checkpoints = match std::fs::File::open(&path) {
+            Ok(file) => serde_json::from_reader(file)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, checkpoints })
+    }
+
+    fn key(user_id: &str, resource: Resource) -> String {
+        format!("{user_id}/{}", resource.as_str())
+    }
+}
+
+impl CheckpointStore for FileStore {
+    fn get(&self, user_id: &str, resource: Resource) -> Result<Option<NaiveDate>> {
+        Ok(self.checkpoints.get(&Self::key(user_id, resource)).copied())
+    }
+
+    fn set(&mut self, user_id: &str, resource: Resource, date: NaiveDate) -> Result<()> {
+        self.checkpoints.insert(Self::key(user_id, resource), date);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(&self.path)?;
+        serde_json::to_writer_pretty(file, &self.checkpoints)?;
+        Ok(())
+    }
+}
+
+/// Fetches new data since the last checkpoint.
+pub struct Syncer<'client, S> {
+    client: &'client Client,
+    store: S,
+    trailing_days: u32,
+    initial_days: u32,
+}
+
+impl<'client, S: CheckpointStore> Syncer<'client, S> {
+    pub fn new(client: &'client Client, store: S) -> Self {
+        Self {
+            client,
+            store,
+            trailing_days: DEFAULT_TRAILING_DAYS,
+            initial_days: DEFAULT_INITIAL_DAYS,
+        }
+    }
+
+    /// Number of already synced days to fetch again on every run.
+    pub fn with_trailing_days(mut self, days: u32) -> Self {
+        self.trailing_days = days;
+        self
+    }
+
+    /// Number of days up to today to fetch when there's no checkpoint yet.
+    pub fn with_initial_days(mut self, days: u32) -> Self {
+        self.initial_days = days;
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Sync a single resource, returning the new and re-fetched records in
+    /// date order. The checkpoint is only updated if fetching succeeds.
+    pub async fn sync(&mut self, user_id: &str, resource: Resource) -> Result<Vec<Record>> {
+        let today = Utc::now().naive_utc().date();
+        let checkpoint = self.store.get(user_id, resource)?;
+        let Some(range) = sync_range(checkpoint, today, self.trailing_days, self.initial_days)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let records = dedup(self.fetch(user_id, resource, range).await?);
+        self.store
+            .commit(user_id, resource, range.end(), &records)?;
+        Ok(records)
+    }
+
+    /// Sync every [`Resource`], one after another.
+    pub async fn sync_all(&mut self, user_id: &str) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        for resource in Resource::ALL {
+            records.extend(self.sync(user_id, resource).await?);
+        }
+        Ok(records)
+    }
+
+    async fn fetch(
+        &self,
+        user_id: &str,
+        resource: Resource,
+        range: DateRange,
+    ) -> Result<Vec<Record>> {
+        let user = Some(user_id);
+        let records = match resource {
+            Resource::Weight => self
+                .client
+                .body_time_series()
+                .get_weight_time_series_by_date_range(range, user)
+                .await?
+                .into_iter()
+                .map(Record::Weight)
+                .collect(),
+            Resource::Sleep => self
+                .client
+                .sleep()
+                .get_sleep_log_by_date_range(range, user)
+                .await?
+                .into_iter()
+                .map(|sleep| Record::Sleep(Box::new(sleep)))
+                .collect(),
+            Resource::HeartRate => self
+                .client
+                .heart_rate()
+                .get_heart_rate_time_series_by_date_range(range, user)
+                .await?
+                .into_iter()
+                .map(Record::HeartRate)
+                .collect(),
+            Resource::Activity => {
+                let mut records = Vec::new();
+                for date in range.start().iter_days().take(range.days() as usize) {
+                    let summary = self
+                        .client
+                        .activity()
+                        .get_daily_activity_summary(Some(date), user)
+                        .await?;
+                    records.push(Record::Activity { date, summary });
+                }
+                records
+            }
+        };
+        Ok(records)
+    }
+}
+
+/// Range of days to fetch, or `None` if there's nothing to do.
+fn sync_range(
+    checkpoint: Option<NaiveDate>,
+    today: NaiveDate,
+    trailing_days: u32,
+    initial_days: u32,
+) -> Option<DateRange> {
+    let start = match checkpoint {
+        Some(checkpoint) => checkpoint + Duration::days(1) - Duration::days(trailing_days.into()),
+        None => return Some(DateRange::ending_at(today, initial_days)),
+    };
+    DateRange::new(start, today).ok()
+}
+
+/// Drop records whose key was already seen, keeping the first.
+fn dedup(records: Vec<Record>) -> Vec<Record> {
+    let mut seen = HashSet::new();
+    records
+        .into_iter()
+        .filter(|record| seen.insert(record.key()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn first_sync_range() {
+        let range = sync_range(None, date("2022-03-31"), 3, 31).unwrap();
+        assert_eq!(range.start(), date("2022-03-01"));
+        assert_eq!(range.end(), date("2022-03-31"));
+    }
+
+    #[test]
+    fn trailing_window() {
+        let range = sync_range(Some(date("2022-03-10")), date("2022-03-12"), 3, 31).unwrap();
+        assert_eq!(range.start(), date("2022-03-08"));
+        assert_eq!(range.end(), date("2022-03-12"));
+
+        let range = sync_range(Some(date("2022-03-10")), date("2022-03-12"), 0, 31).unwrap();
+        assert_eq!(range.start(), date("2022-03-11"));
+    }
+
+    #[test]
+    fn nothing_to_sync() {
+        assert!(sync_range(Some(date("2022-03-12")), date("2022-03-12"), 0, 31).is_none());
+    }
+
+    #[test]
+    fn dedup_by_key() {
+        let log = |log_id| {
+            Record::Weight(WeightLog {
+                bmi: 23.57,
+                date: date("2022-03-10"),
+                log_id,
+                time: "08:00:00".parse().unwrap(),
+                weight: 73.0,
+                source: None,
+                extra: Default::default(),
+            })
+        };
+        let records = dedup(vec![log(1), log(2), log(1)]);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].key(), RecordKey::LogId(2));
+    }
+
+    #[test]
+    fn file_store() {
+        let path = std::env::temp_dir().join(format!("fitbit-rs-sync-{}.json", std::process::id()));
+        let mut store = FileStore::open(&path).unwrap();
+        store
+            .set("ABC", Resource::Sleep, date("2022-03-10"))
+            .unwrap();
+
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(
+            store.get("ABC", Resource::Sleep).unwrap(),
+            Some(date("2022-03-10"))
+        );
+        assert_eq!(store.get("ABC", Resource::Weight).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
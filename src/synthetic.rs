@@ -0,0 +1,630 @@
+//! Realistic, made up data for demos, tests and load testing.
+//!
+//! A [`User`] generates weight and body fat logs, sleep with stages, minute
+//! by minute heart rate, daily steps and paired devices for a person who
+//! doesn't exist. Everything is derived from the seed and the date asked
+//! for, so the same seed always gives the same data, regardless of which
+//! ranges it's requested in.
+//!
+//! Each kind of data is available both as the crate's models and as JSON
+//! shaped like the matching Fitbit response, ready to be served by a mock
+//! API:
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use fitbit_rs::{synthetic::User, DateRange};
+//!
+//! let user = User::new(42);
+//! let week = DateRange::ending_at(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(), 7);
+//! let steps = user.steps(week);
+//! assert_eq!(steps.len(), 7);
+//! assert_eq!(user.steps_json(week)["activities-steps"][0]["value"], steps[0].value);
+//! ```
+//!
+//! Weights are in kilograms and heights in metres, as Fitbit reports them
+//! for the metric unit system.
+
+use std::f64::consts::TAU;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde_json::{json, Value};
+
+use crate::{
+    date_range::DateRange,
+    models::{
+        activity::time_series::Entry,
+        body::{fat::FatLog, weight::WeightLog},
+        devices::{BatteryLevel, Device, DeviceInfo, DeviceType},
+        heart_rate::{
+            intraday_time_series::{DayEntry, Intraday, IntradayEntry, Value as HeartRateValue},
+            HeartRateZone,
+        },
+        sleep::list::{LevelSummary, Levels, Sleep, SleepDataPoint, SleepLevel, Summary},
+        Extra,
+    },
+};
+
+/// Kinds of data, so each gets its own random stream.
+#[derive(Clone, Copy)]
+enum Stream {
+    Profile = 1,
+    Weight,
+    Sleep,
+    HeartRate,
+    Exercise,
+    Steps,
+    Devices,
+}
+
+/// A made up person.
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+    seed: u64,
+    /// Age in years, which sets the heart rate zones.
+    pub age: u32,
+    /// Height in metres.
+    pub height: f64,
+    /// Weight the daily weight drifts around, in kilograms.
+    pub weight: f64,
+    /// Body fat percentage the daily body fat drifts around.
+    pub fat: f64,
+    /// Lowest heart rate, reached during sleep.
+    pub resting_heart_rate: f64,
+    /// Steps on an ordinary weekday without exercise.
+    pub daily_steps: f64,
+}
+
+impl User {
+    /// A person whose profile and data are all derived from `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed, Stream::Profile, 0);
+        let height = rng.range(1.55, 1.95);
+        let bmi = rng.range(20.0, 29.0);
+        Self {
+            seed,
+            age: rng.range(20.0, 65.0) as u32,
+            height,
+            weight: bmi * height * height,
+            fat: rng.range(12.0, 32.0),
+            resting_heart_rate: rng.range(48.0, 68.0),
+            daily_steps: rng.range(4_000.0, 11_000.0),
+        }
+    }
+
+    /// Weight and body fat logged on `date`, if the user weighed themselves
+    /// that day. Most days they do, in the morning.
+    pub fn weighing(&self, date: NaiveDate) -> Option<(WeightLog, FatLog)> {
+        let mut rng = Rng::new(self.seed, Stream::Weight, day(date));
+        if rng.unit() < 0.15 {
+            return None;
+        }
+        let time = NaiveTime::from_num_seconds_from_midnight_opt(
+            rng.range(6.0 * 3600.0, 8.5 * 3600.0) as u32,
+            0,
+        )?;
+        let log_id = date.and_time(time).and_utc().timestamp_millis() as u64;
+
+        let weight = round(
+            self.weight + self.drift(date, 2.5, 1.5) + rng.normal() * 0.3,
+            1,
+        );
+        let fat = round(
+            self.fat + self.drift(date, 1.5, 1.0) + rng.normal() * 0.4,
+            2,
+        );
+        let weight_log = WeightLog {
+            bmi: round(weight / (self.height * self.height), 2) as f32,
+            date,
+            log_id,
+            time,
+            weight: weight as f32,
+            source: Some("Aria".to_owned()),
+            extra: Extra::default(),
+        };
+        let fat_log = FatLog {
+            date,
+            fat: fat as f32,
+            log_id,
+            time,
+            source: "Aria".to_owned(),
+            extra: Extra::default(),
+        };
+        Some((weight_log, fat_log))
+    }
+
+    /// Weight logs within `range`, oldest first.
+    pub fn weight_logs(&self, range: DateRange) -> Vec<WeightLog> {
+        range
+            .dates()
+            .filter_map(|date| self.weighing(date))
+            .map(|(weight, _)| weight)
+            .collect()
+    }
+
+    /// Body fat logs within `range`, oldest first.
+    pub fn fat_logs(&self, range: DateRange) -> Vec<FatLog> {
+        range
+            .dates()
+            .filter_map(|date| self.weighing(date))
+            .map(|(_, fat)| fat)
+            .collect()
+    }
+
+    /// The night of sleep ending on `date`.
+    ///
+    /// The user falls asleep late in the evening and goes through four to
+    /// six cycles of light, deep and REM sleep, with deep sleep early in
+    /// the night and REM later, and the odd brief awakening.
+    pub fn sleep(&self, date: NaiveDate) -> Sleep {
+        let mut rng = Rng::new(self.seed, Stream::Sleep, day(date));
+        let start_time = date.pred_opt().unwrap_or(date).and_time(NaiveTime::MIN)
+            + Duration::minutes(rng.range(22.0 * 60.0, 24.5 * 60.0) as i64);
+
+        let mut stages = Stages::default();
+        stages.push(SleepLevel::Wake, rng.range(2.0, 12.0));
+        let cycles = rng.range(4.0, 7.0) as u32;
+        for cycle in 0..cycles {
+            let progress = f64::from(cycle) / f64::from(cycles);
+            stages.push(SleepLevel::Light, rng.range(15.0, 35.0));
+            stages.push(
+                SleepLevel::Deep,
+                (1.0 - progress) * rng.range(25.0, 45.0) + 5.0,
+            );
+            stages.push(SleepLevel::Light, rng.range(10.0, 25.0));
+            stages.push(SleepLevel::Rem, progress * rng.range(20.0, 35.0) + 5.0);
+            if rng.unit() < 0.4 {
+                stages.push(SleepLevel::Wake, rng.range(1.0, 6.0));
+            }
+        }
+        stages.push(SleepLevel::Wake, rng.range(1.0, 8.0));
+
+        let mut time = start_time;
+        let data = stages
+            .0
+            .iter()
+            .map(|(level, minutes)| {
+                let point = SleepDataPoint {
+                    date_time: time,
+                    level: level.clone(),
+                    seconds: minutes * 60,
+                    extra: Extra::default(),
+                };
+                time += Duration::minutes(*minutes as i64);
+                point
+            })
+            .collect();
+
+        let time_in_bed = stages.minutes(None);
+        let minutes_awake = stages.minutes(Some(&SleepLevel::Wake));
+        let minutes_asleep = time_in_bed - minutes_awake;
+        let summary = |level| Some(stages.summary(&level));
+        Sleep {
+            date_of_sleep: date,
+            duration_millis: time_in_bed * 60_000,
+            efficiency: minutes_asleep * 100 / time_in_bed,
+            end_time: time,
+            info_code: 0,
+            is_main_sleep: true,
+            levels: Levels {
+                summary: LevelSummary {
+                    deep: summary(SleepLevel::Deep),
+                    light: summary(SleepLevel::Light),
+                    rem: summary(SleepLevel::Rem),
+                    wake: summary(SleepLevel::Wake),
+                    asleep: None,
+                    awake: None,
+                    restless: None,
+                    extra: Extra::default(),
+                },
+                data,
+                short_data: Vec::new(),
+                extra: Extra::default(),
+            },
+            log_id: start_time.and_utc().timestamp() as u64,
+            minutes_after_wakeup: 0,
+            minutes_asleep,
+            minutes_awake,
+            minutes_to_fall_asleep: 0,
+            start_time,
+            time_in_bed,
+            type_: "stages".to_owned(),
+            extra: Extra::default(),
+        }
+    }
+
+    /// Sleep ending within `range`, oldest first.
+    pub fn sleeps(&self, range: DateRange) -> Vec<Sleep> {
+        range.dates().map(|date| self.sleep(date)).collect()
+    }
+
+    /// Heart rate for every minute of `date`.
+    ///
+    /// It follows a daily rhythm, lowest in the early morning and highest
+    /// in the afternoon, drops further during sleep and rises sharply
+    /// during exercise.
+    pub fn heart_rate(&self, date: NaiveDate) -> Intraday {
+        let mut rng = Rng::new(self.seed, Stream::HeartRate, day(date));
+        let wake_up = self.sleep(date).end_time.time();
+        let bedtime = self.sleep(date + Duration::days(1)).start_time;
+        let exercise = self.exercise(date);
+
+        let dataset = (0..24 * 60)
+            .map(|minute| {
+                let time = NaiveTime::MIN + Duration::minutes(minute);
+                // Lowest at 04:00, highest at 16:00.
+                let rhythm = 0.5 - 0.5 * (TAU * (minute as f64 - 240.0) / 1440.0).cos();
+                let mut bpm = self.resting_heart_rate + 4.0 + 12.0 * rhythm + rng.normal() * 2.0;
+                if time < wake_up || date.and_time(time) >= bedtime {
+                    bpm -= 6.0;
+                }
+                if let Some((start, length)) = exercise {
+                    if (start..start + length).contains(&minute) {
+                        bpm += 45.0 + rng.range(0.0, 30.0);
+                    }
+                }
+                IntradayEntry {
+                    time,
+                    value: bpm.max(self.resting_heart_rate - 5.0).round() as usize,
+                    extra: Extra::default(),
+                }
+            })
+            .collect();
+        Intraday {
+            dataset,
+            dataset_interval: 1,
+            dataset_type: "minute".to_owned(),
+            extra: Extra::default(),
+        }
+    }
+
+    /// Daily heart rate summary for `date`: the minutes spent in each zone
+    /// and the resting heart rate.
+    pub fn heart_rate_summary(&self, date: NaiveDate) -> DayEntry {
+        self.summarize_heart_rate(date, &self.heart_rate(date))
+    }
+
+    fn summarize_heart_rate(&self, date: NaiveDate, intraday: &Intraday) -> DayEntry {
+        let max = 220 - self.age as usize;
+        let bounds = [30, max / 2, max * 7 / 10, max * 85 / 100, max];
+        let names = ["Out of Range", "Fat Burn", "Cardio", "Peak"];
+        let heart_rate_zones = names
+            .iter()
+            .zip(bounds.windows(2))
+            .map(|(name, bounds)| HeartRateZone {
+                calories_out: None,
+                max: bounds[1],
+                min: bounds[0],
+                minutes: Some(
+                    intraday
+                        .dataset
+                        .iter()
+                        .filter(|e| (bounds[0]..bounds[1]).contains(&e.value))
+                        .count(),
+                ),
+                name: (*name).to_owned(),
+                extra: Extra::default(),
+            })
+            .collect();
+        DayEntry {
+            date_time: date,
+            value: HeartRateValue {
+                custom_heart_rate_zones: Vec::new(),
+                heart_rate_zones,
+                value: Some(self.resting_heart_rate.round() as usize),
+                extra: Extra::default(),
+            },
+            extra: Extra::default(),
+        }
+    }
+
+    /// Steps on `date`. Weekends are a little lazier and exercise adds a
+    /// few thousand.
+    pub fn daily_steps(&self, date: NaiveDate) -> u64 {
+        let mut rng = Rng::new(self.seed, Stream::Steps, day(date));
+        let mut steps = self.daily_steps * rng.range(0.6, 1.4);
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            steps *= 0.8;
+        }
+        if let Some((_, length)) = self.exercise(date) {
+            steps += length as f64 * rng.range(80.0, 140.0);
+        }
+        steps.round() as u64
+    }
+
+    /// Daily step counts within `range`, oldest first.
+    pub fn steps(&self, range: DateRange) -> Vec<Entry> {
+        range
+            .dates()
+            .map(|date| Entry {
+                date_time: date,
+                value: self.daily_steps(date).to_string(),
+                extra: Extra::default(),
+            })
+            .collect()
+    }
+
+    /// A tracker and a scale, as they would be seen at `now`.
+    pub fn devices(&self, now: NaiveDateTime) -> Vec<DeviceInfo> {
+        let mut rng = Rng::new(self.seed, Stream::Devices, 0);
+        let tracker_id = rng.next_u64() % 1_000_000_000;
+        let scale_id = rng.next_u64();
+        let mut rng = Rng::new(self.seed, Stream::Devices, now.and_utc().timestamp() as u64);
+
+        let battery = rng.range(5.0, 100.0) as usize;
+        let tracker = DeviceInfo {
+            battery: match battery {
+                0..=9 => BatteryLevel::Empty,
+                10..=24 => BatteryLevel::Low,
+                25..=59 => BatteryLevel::Medium,
+                _ => BatteryLevel::High,
+            },
+            battery_percentage: battery,
+            device_version: Device::Charge5,
+            id: tracker_id.to_string(),
+            last_sync_time: now - Duration::minutes(rng.range(1.0, 90.0) as i64),
+            ty: DeviceType::Tracker,
+            extra: Extra::default(),
+        };
+
+        // The scale syncs when the user last weighed themselves.
+        let last_weighing = now
+            .date()
+            .iter_days()
+            .rev()
+            .take(30)
+            .filter_map(|date| self.weighing(date))
+            .map(|(weight, _)| weight.date.and_time(weight.time))
+            .find(|time| *time <= now)
+            .unwrap_or(now - Duration::days(30));
+        let scale = DeviceInfo {
+            battery: BatteryLevel::High,
+            battery_percentage: 100,
+            device_version: Device::Aria,
+            id: format!("{scale_id:016X}"),
+            last_sync_time: last_weighing,
+            ty: DeviceType::Scale,
+            extra: Extra::default(),
+        };
+        vec![tracker, scale]
+    }
+
+    /// Response of the weight log endpoints for `range`.
+    pub fn weight_json(&self, range: DateRange) -> Value {
+        json!({ "weight": self.weight_logs(range) })
+    }
+
+    /// Response of the body fat log endpoints for `range`.
+    pub fn fat_json(&self, range: DateRange) -> Value {
+        json!({ "fat": self.fat_logs(range) })
+    }
+
+    /// Response of the sleep log endpoints for `range`. Like Fitbit, sleep
+    /// is listed newest first.
+    pub fn sleep_json(&self, range: DateRange) -> Value {
+        let mut sleep = self.sleeps(range);
+        sleep.reverse();
+        json!({
+            "sleep": sleep,
+            "summary": {
+                "totalMinutesAsleep": sleep.iter().map(|s| s.minutes_asleep).sum::<u64>(),
+                "totalSleepRecords": sleep.len(),
+                "totalTimeInBed": sleep.iter().map(|s| s.time_in_bed).sum::<u64>(),
+            },
+        })
+    }
+
+    /// Response of the intraday heart rate endpoint for `date`, at one
+    /// minute detail.
+    pub fn heart_rate_json(&self, date: NaiveDate) -> Value {
+        let intraday = self.heart_rate(date);
+        json!({
+            "activities-heart": [self.summarize_heart_rate(date, &intraday)],
+            "activities-heart-intraday": intraday,
+        })
+    }
+
+    /// Response of the heart rate time series endpoints for `range`.
+    pub fn heart_rate_summaries_json(&self, range: DateRange) -> Value {
+        let series: Vec<_> = range
+            .dates()
+            .map(|date| self.heart_rate_summary(date))
+            .collect();
+        json!({ "activities-heart": series })
+    }
+
+    /// Response of the steps time series endpoints for `range`.
+    pub fn steps_json(&self, range: DateRange) -> Value {
+        json!({ "activities-steps": self.steps(range) })
+    }
+
+    /// Response of the devices endpoint at `now`.
+    pub fn devices_json(&self, now: NaiveDateTime) -> Value {
+        json!(self.devices(now))
+    }
+
+    /// Slow, smooth wandering of up to about `yearly + monthly` around
+    /// zero, mixing a yearly and a roughly monthly wave.
+    fn drift(&self, date: NaiveDate, yearly: f64, monthly: f64) -> f64 {
+        let mut rng = Rng::new(self.seed, Stream::Weight, 0);
+        let day = f64::from(date.num_days_from_ce());
+        yearly * (TAU * day / 365.0 + rng.range(0.0, TAU)).sin()
+            + monthly * (TAU * day / 33.0 + rng.range(0.0, TAU)).sin()
+    }
+
+    /// Start and length, in minutes after midnight, of exercise on `date`,
+    /// if any.
+    fn exercise(&self, date: NaiveDate) -> Option<(i64, i64)> {
+        let mut rng = Rng::new(self.seed, Stream::Exercise, day(date));
+        if rng.unit() < 0.4 {
+            return None;
+        }
+        let start = rng.range(7.0 * 60.0, 19.0 * 60.0) as i64;
+        let length = rng.range(20.0, 75.0) as i64;
+        Some((start, length))
+    }
+}
+
+/// Sleep stages in order, with their length in whole minutes.
+#[derive(Default)]
+struct Stages(Vec<(SleepLevel, u64)>);
+
+impl Stages {
+    /// Append a stage, merging it into the last one if it's the same level.
+    fn push(&mut self, level: SleepLevel, minutes: f64) {
+        let minutes = minutes.round().max(1.0) as u64;
+        match self.0.last_mut() {
+            Some((last, total)) if *last == level => *total += minutes,
+            _ => self.0.push((level, minutes)),
+        }
+    }
+
+    /// Minutes spent at `level`, or in total.
+    fn minutes(&self, level: Option<&SleepLevel>) -> u64 {
+        self.0
+            .iter()
+            .filter(|(l, _)| level.is_none_or(|level| l == level))
+            .map(|(_, minutes)| minutes)
+            .sum()
+    }
+
+    fn summary(&self, level: &SleepLevel) -> Summary {
+        Summary {
+            count: self.0.iter().filter(|(l, _)| l == level).count() as u64,
+            minutes: self.minutes(Some(level)),
+            thirty_day_avg_minutes: None,
+            extra: Extra::default(),
+        }
+    }
+}
+
+fn day(date: NaiveDate) -> u64 {
+    date.num_days_from_ce() as u64
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// SplitMix64, which is plenty for made up data and stable across
+/// platforms and releases.
+struct Rng(u64);
+
+impl Rng {
+    /// A generator for one kind of data on one day.
+    fn new(seed: u64, stream: Stream, day: u64) -> Self {
+        let mut rng = Self(seed);
+        let stream = Self(rng.next_u64() ^ stream as u64).next_u64();
+        Self(stream ^ day.wrapping_mul(0xD1B5_4A32_D192_ED03))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[low, high)`.
+    fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.unit()
+    }
+
+    /// Roughly standard normal.
+    fn normal(&mut self) -> f64 {
+        (0..12).map(|_| self.unit()).sum::<f64>() - 6.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::heart_rate::intraday_time_series::Response;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn deterministic() {
+        let month = DateRange::new(date(1), date(31)).unwrap();
+        let week = DateRange::new(date(10), date(16)).unwrap();
+
+        assert_eq!(User::new(7), User::new(7));
+        assert_ne!(User::new(7), User::new(8));
+        assert_eq!(
+            User::new(7).weight_json(month),
+            User::new(7).weight_json(month)
+        );
+
+        // A day's data doesn't depend on the range it's part of.
+        let user = User::new(7);
+        let logs = user.weight_logs(month);
+        for log in user.weight_logs(week) {
+            assert!(logs.contains(&log));
+        }
+        assert!(logs.len() > 20 && logs.len() < 31);
+    }
+
+    #[test]
+    fn realistic() {
+        let user = User::new(1);
+        let month = DateRange::new(date(1), date(31)).unwrap();
+
+        for pair in user.weight_logs(month).windows(2) {
+            assert!((pair[0].weight - pair[1].weight).abs() < 2.5);
+        }
+
+        for sleep in user.sleeps(month) {
+            assert!((5 * 60..10 * 60).contains(&sleep.minutes_asleep));
+            assert_eq!(
+                sleep.start_time + Duration::minutes(sleep.time_in_bed as i64),
+                sleep.end_time
+            );
+            let deep = sleep.levels.summary.deep.unwrap().minutes;
+            assert!(deep > 30);
+        }
+
+        let heart_rate = user.heart_rate(date(5));
+        assert_eq!(heart_rate.dataset.len(), 24 * 60);
+        let at = |hour: usize| heart_rate.dataset[hour * 60..hour * 60 + 60].iter();
+        let night = at(3).map(|e| e.value).sum::<usize>();
+        let afternoon = at(15).map(|e| e.value).sum::<usize>();
+        assert!(night < afternoon);
+
+        let devices = user.devices(date(5).and_hms_opt(12, 0, 0).unwrap());
+        assert_eq!(devices[1].ty, DeviceType::Scale);
+        assert_eq!(devices[1].last_sync_time.date(), date(5));
+    }
+
+    #[test]
+    fn json_matches_models() {
+        let user = User::new(3);
+        let week = DateRange::new(date(1), date(7)).unwrap();
+
+        let response: Response = serde_json::from_value(user.heart_rate_json(date(2))).unwrap();
+        assert_eq!(response.intraday, user.heart_rate(date(2)));
+        assert_eq!(
+            response.daily_series.unwrap()[0],
+            user.heart_rate_summary(date(2))
+        );
+
+        let sleep: Vec<Sleep> =
+            serde_json::from_value(user.sleep_json(week)["sleep"].clone()).unwrap();
+        assert_eq!(sleep[0], user.sleep(date(7)));
+
+        let now = date(7).and_hms_opt(9, 0, 0).unwrap();
+        let devices: Vec<DeviceInfo> = serde_json::from_value(user.devices_json(now)).unwrap();
+        assert_eq!(devices, user.devices(now));
+
+        let weight: Vec<WeightLog> =
+            serde_json::from_value(user.weight_json(week)["weight"].clone()).unwrap();
+        assert_eq!(weight, user.weight_logs(week));
+    }
+}
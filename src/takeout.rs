@@ -0,0 +1,588 @@
+//! Import a Fitbit data export, as downloaded from Fitbit or Google Takeout.
+//!
+//! An export holds one JSON file per kind of data and day, such as
+//! `weight-2020-01-01.json`, `sleep-2020-01-01.json` or
+//! `heart_rate-2020-01-01.json`, spread over a few directories. An
+//! [`Archive`] reads the extracted directory or, with the `zip` feature, the
+//! zip file itself, and turns the files it recognizes into the same models
+//! the API returns:
+//!
+//! ```no_run
+//! # fn run() -> fitbit_rs::Result<()> {
+//! use fitbit_rs::takeout::{Archive, Record};
+//!
+//! for record in Archive::open("takeout-20240101T000000Z-001.zip")?.records() {
+//!     if let Record::Weight(log) = record? {
+//!         println!("{} {}", log.date, log.weight);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Exports store heart rate and minute by minute activity in UTC, while the
+//! API uses the user's local time. Use [`Archive::with_timezone`] so they
+//! line up; otherwise UTC is kept. Activity is summed into daily totals
+//! like the activity time series endpoints return, and is only yielded
+//! once every file has been read.
+//!
+//! Weights are in the units the account was set to use when exporting.
+//! Distances are in kilometres.
+//!
+//! Only the JSON files are read. The CSV files exports also hold, such as
+//! sleep scores, stress scores or heart rate variability, are skipped like
+//! any other unrecognized file.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::{
+    error::Error,
+    models::{
+        activity::time_series::{Entry, Resource},
+        body::{fat::FatLog, weight::WeightLog},
+        heart_rate::intraday_time_series::IntradayEntry,
+        sleep::list::Sleep,
+        Extra,
+    },
+    timezone::UserTimezone,
+    Result,
+};
+
+/// A record read from an export.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Weight(WeightLog),
+    /// Body fat, which exports log together with the weight.
+    Fat(FatLog),
+    Sleep(Box<Sleep>),
+    /// A heart rate reading on `date`.
+    HeartRate {
+        date: NaiveDate,
+        entry: IntradayEntry,
+    },
+    /// Daily total of an activity resource.
+    Activity {
+        resource: Resource,
+        entry: Entry,
+    },
+}
+
+/// What a file in the export holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Weight,
+    Sleep,
+    HeartRate,
+    /// Values logged every minute, to be added up per day.
+    Minutes(Resource, Scale),
+    /// Values already totalled per day.
+    Daily(Resource),
+}
+
+/// Factor to multiply exported values by to get the API's units.
+type Scale = f64;
+
+impl Kind {
+    /// Recognize files named `{kind}-{YYYY-MM-DD}.json`.
+    fn from_file_name(name: &str) -> Option<Self> {
+        let stem = name.strip_suffix(".json")?;
+        // Names from other products may not be ASCII, so `split_at` could
+        // land inside a character.
+        let split = stem.len().checked_sub(10)?;
+        let (kind, date) = (stem.get(..split)?, stem.get(split..)?);
+        let kind = kind.strip_suffix('-')?;
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+
+        Some(match kind {
+            "weight" => Kind::Weight,
+            "sleep" => Kind::Sleep,
+            "heart_rate" => Kind::HeartRate,
+            "steps" => Kind::Minutes(Resource::Steps, 1.0),
+            "calories" => Kind::Minutes(Resource::Calories, 1.0),
+            // Centimetres.
+            "distance" => Kind::Minutes(Resource::Distance, 1e-5),
+            "sedentary_minutes" => Kind::Daily(Resource::Sedentary),
+            "lightly_active_minutes" => Kind::Daily(Resource::LightlyActive),
+            "moderately_active_minutes" => Kind::Daily(Resource::FairlyActive),
+            "very_active_minutes" => Kind::Daily(Resource::VeryActive),
+            _ => return None,
+        })
+    }
+}
+
+enum Source {
+    Directory,
+    #[cfg(feature = "zip")]
+    Zip(zip::ZipArchive<fs::File>),
+}
+
+/// An extracted export directory or export zip file.
+pub struct Archive {
+    source: Source,
+    /// Files to read, as paths or names within the zip file.
+    files: Vec<(String, Kind)>,
+    timezone: UserTimezone,
+}
+
+impl Archive {
+    /// Open the export at `path`, either a directory or, with the `zip`
+    /// feature, a zip file.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let (source, files) = if path.is_dir() {
+            let mut files = Vec::new();
+            walk(path, &mut files)?;
+            (Source::Directory, files)
+        } else {
+            Self::open_zip(path)?
+        };
+
+        let files = files
+            .into_iter()
+            .filter_map(|file| {
+                let kind = Kind::from_file_name(file.rsplit(['/', '\\']).next()?)?;
+                Some((file, kind))
+            })
+            .collect();
+        Ok(Self {
+            source,
+            files,
+            timezone: UserTimezone::from_offset(FixedOffset::east_opt(0).unwrap()),
+        })
+    }
+
+    #[cfg(feature = "zip")]
+    fn open_zip(path: &Path) -> Result<(Source, Vec<String>)> {
+        let zip = zip::ZipArchive::new(fs::File::open(path)?).map_err(std::io::Error::from)?;
+        let mut files: Vec<_> = zip.file_names().map(str::to_owned).collect();
+        files.sort();
+        Ok((Source::Zip(zip), files))
+    }
+
+    #[cfg(not(feature = "zip"))]
+    fn open_zip(path: &Path) -> Result<(Source, Vec<String>)> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "{} is not a directory, and zip files need the zip feature",
+                path.display()
+            ),
+        )
+        .into())
+    }
+
+    /// Convert heart rate and activity times from UTC to `timezone`.
+    pub fn with_timezone(mut self, timezone: UserTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Read every recognized file, one at a time.
+    ///
+    /// A file which can't be read yields an [`Error::Import`] naming it, and
+    /// reading carries on with the next file.
+    pub fn records(&mut self) -> impl Iterator<Item = Result<Record>> + '_ {
+        let mut files = std::mem::take(&mut self.files).into_iter();
+        let mut pending = Vec::new().into_iter();
+        let mut activity = BTreeMap::new();
+
+        std::iter::from_fn(move || loop {
+            if let Some(record) = pending.next() {
+                return Some(Ok(record));
+            }
+            let Some((path, kind)) = files.next() else {
+                // Daily totals are complete once every file has been read.
+                pending = std::mem::take(&mut activity)
+                    .into_iter()
+                    .map(|((resource, date), total)| activity_record(resource, date, total))
+                    .collect::<Vec<_>>()
+                    .into_iter();
+                return pending.next().map(Ok);
+            };
+
+            match self.read(&path, kind, &mut activity) {
+                Ok(records) => pending = records.into_iter(),
+                Err(err) => {
+                    return Some(Err(Error::Import {
+                        path,
+                        source: Box::new(err),
+                    }))
+                }
+            }
+        })
+    }
+
+    /// Read one file, returning its records or adding to the activity
+    /// totals.
+    fn read(
+        &mut self,
+        path: &str,
+        kind: Kind,
+        activity: &mut BTreeMap<(Resource, NaiveDate), f64>,
+    ) -> Result<Vec<Record>> {
+        let bytes = match &mut self.source {
+            Source::Directory => fs::read(path)?,
+            #[cfg(feature = "zip")]
+            Source::Zip(zip) => {
+                let mut bytes = Vec::new();
+                let mut file = zip.by_name(path).map_err(std::io::Error::from)?;
+                std::io::Read::read_to_end(&mut file, &mut bytes)?;
+                bytes
+            }
+        };
+
+        Ok(match kind {
+            Kind::Weight => parse::<ExportedWeight>(&bytes)?
+                .into_iter()
+                .flat_map(ExportedWeight::into_records)
+                .collect(),
+            Kind::Sleep => parse::<serde_json::Value>(&bytes)?
+                .into_iter()
+                .map(|mut sleep| {
+                    // Exports name this field differently to the API.
+                    if let Some(object) = sleep.as_object_mut() {
+                        if let Some(main) = object.remove("mainSleep") {
+                            object.entry("isMainSleep").or_insert(main);
+                        }
+                    }
+                    Ok(Record::Sleep(Box::new(serde_json::from_value(sleep)?)))
+                })
+                .collect::<Result<_>>()?,
+            Kind::HeartRate => parse::<ExportedHeartRate>(&bytes)?
+                .into_iter()
+                .map(|reading| {
+                    let local = self.timezone.local_time(reading.date_time);
+                    Record::HeartRate {
+                        date: local.date(),
+                        entry: IntradayEntry {
+                            time: local.time(),
+                            value: reading.value.bpm,
+                            extra: reading.value.extra,
+                        },
+                    }
+                })
+                .collect(),
+            Kind::Minutes(resource, scale) => {
+                for value in parse::<ExportedValue>(&bytes)? {
+                    let date = self.timezone.local_time(value.date_time).date();
+                    *activity.entry((resource, date)).or_default() += value.value()? * scale;
+                }
+                Vec::new()
+            }
+            Kind::Daily(resource) => {
+                for value in parse::<ExportedValue>(&bytes)? {
+                    *activity
+                        .entry((resource, value.date_time.date()))
+                        .or_default() += value.value()?;
+                }
+                Vec::new()
+            }
+        })
+    }
+}
+
+/// Collect the paths of every file below `dir`, sorted.
+fn walk(dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<Vec<T>> {
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+fn activity_record(resource: Resource, date: NaiveDate, total: f64) -> Record {
+    let value = match resource {
+        Resource::Distance => format!("{total:.2}"),
+        _ => format!("{}", total.round()),
+    };
+    Record::Activity {
+        resource,
+        entry: Entry {
+            date_time: date,
+            value,
+            extra: Extra::default(),
+        },
+    }
+}
+
+/// Parse the `MM/DD/YY HH:MM:SS` timestamps exports use.
+fn date_time<'de, D>(deserializer: D) -> std::result::Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&s, "%m/%d/%y %H:%M:%S").map_err(serde::de::Error::custom)
+}
+
+/// Parse the `MM/DD/YY` dates exports use.
+fn date<'de, D>(deserializer: D) -> std::result::Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(&s, "%m/%d/%y").map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedWeight {
+    log_id: u64,
+    weight: f32,
+    bmi: f32,
+    fat: Option<f32>,
+    #[serde(deserialize_with = "date")]
+    date: NaiveDate,
+    time: NaiveTime,
+    source: Option<String>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+impl ExportedWeight {
+    fn into_records(self) -> Vec<Record> {
+        let mut records = Vec::with_capacity(2);
+        if let Some(fat) = self.fat {
+            records.push(Record::Fat(FatLog {
+                date: self.date,
+                fat,
+                log_id: self.log_id,
+                time: self.time,
+                source: self.source.clone().unwrap_or_default(),
+                extra: Extra::default(),
+            }));
+        }
+        records.push(Record::Weight(WeightLog {
+            bmi: self.bmi,
+            date: self.date,
+            log_id: self.log_id,
+            time: self.time,
+            weight: self.weight,
+            source: self.source,
+            extra: self.extra,
+        }));
+        records
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedHeartRate {
+    #[serde(deserialize_with = "date_time")]
+    date_time: NaiveDateTime,
+    value: HeartRateValue,
+}
+
+#[derive(Deserialize)]
+struct HeartRateValue {
+    bpm: usize,
+    /// Keeps the reading's `confidence`.
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedValue {
+    #[serde(deserialize_with = "date_time")]
+    date_time: NaiveDateTime,
+    value: String,
+}
+
+impl ExportedValue {
+    fn value(&self) -> Result<f64> {
+        self.value
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid value {:?}", self.value)))
+            .map_err(Error::Serde)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::sleep::list::SleepLevel, util::TempPath};
+
+    const FILES: [(&str, &str); 6] = [
+        (
+            "Global Export Data/weight-2020-01-01.json",
+            r#"[
+                {"logId":1577862754000,"weight":165.3,"bmi":23.72,"fat":21.5,"date":"01/01/20","time":"07:12:34","source":"Aria"},
+                {"logId":1577949154000,"weight":165.0,"bmi":23.68,"date":"01/02/20","time":"07:12:34","source":"API"}
+            ]"#,
+        ),
+        (
+            "Global Export Data/sleep-2020-01-01.json",
+            r#"[{
+                "logId":25304954921,"dateOfSleep":"2020-01-02","startTime":"2020-01-01T23:00:00.000","endTime":"2020-01-02T07:00:00.000",
+                "duration":28800000,"minutesToFallAsleep":0,"minutesAsleep":450,"minutesAwake":30,"minutesAfterWakeup":0,"timeInBed":480,
+                "efficiency":94,"type":"stages","infoCode":0,"logType":"auto_detected","mainSleep":true,
+                "levels":{"summary":{"deep":{"count":4,"minutes":90,"thirtyDayAvgMinutes":0}},
+                "data":[{"dateTime":"2020-01-01T23:00:00.000","level":"wake","seconds":300}]}
+            }]"#,
+        ),
+        (
+            "Global Export Data/heart_rate-2020-01-01.json",
+            r#"[
+                {"dateTime":"01/01/20 23:59:55","value":{"bpm":58,"confidence":3}},
+                {"dateTime":"01/02/20 00:00:05","value":{"bpm":57,"confidence":2}}
+            ]"#,
+        ),
+        (
+            "Global Export Data/steps-2020-01-01.json",
+            r#"[
+                {"dateTime":"01/01/20 22:59:00","value":"100"},
+                {"dateTime":"01/01/20 23:00:00","value":"20"},
+                {"dateTime":"01/01/20 23:01:00","value":"3"}
+            ]"#,
+        ),
+        (
+            "Global Export Data/very_active_minutes-2020-01-01.json",
+            r#"[{"dateTime":"01/01/20 00:00:00","value":"35"}]"#,
+        ),
+        ("Global Export Data/Profile.json", r#"{"unrelated":true}"#),
+    ];
+
+    fn write_directory() -> TempPath {
+        let dir = TempPath(
+            std::env::temp_dir().join(format!("fitbit-rs-takeout-{}", std::process::id())),
+        );
+        for (name, contents) in FILES {
+            let path = dir.0.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn import(path: &Path) -> Vec<Record> {
+        let tz = UserTimezone::from_offset(FixedOffset::east_opt(3600).unwrap());
+        Archive::open(path)
+            .unwrap()
+            .with_timezone(tz)
+            .records()
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    fn check(records: &[Record]) {
+        let date = |day| NaiveDate::from_ymd_opt(2020, 1, day).unwrap();
+
+        // Files are read in order of their names.
+        let Record::HeartRate { date: first, entry } = &records[0] else {
+            panic!("{:?}", records[0]);
+        };
+        assert_eq!((*first, entry.value), (date(2), 58));
+        assert_eq!(entry.time, NaiveTime::from_hms_opt(0, 59, 55).unwrap());
+        assert_eq!(entry.extra["confidence"], 3);
+
+        let Record::Sleep(sleep) = &records[2] else {
+            panic!("{:?}", records[2]);
+        };
+        assert!(sleep.is_main_sleep);
+        assert_eq!(sleep.levels.data[0].level, SleepLevel::Wake);
+        assert_eq!(sleep.extra["logType"], "auto_detected");
+
+        assert!(matches!(&records[3], Record::Fat(log) if log.fat == 21.5));
+        assert!(matches!(&records[4], Record::Weight(log) if log.date == date(1)));
+        assert!(matches!(&records[5], Record::Weight(log) if log.source.as_deref() == Some("API")));
+
+        let activity: Vec<_> = records[6..]
+            .iter()
+            .map(|record| match record {
+                Record::Activity { resource, entry } => {
+                    (*resource, entry.date_time, entry.value.as_str())
+                }
+                record => panic!("{record:?}"),
+            })
+            .collect();
+        assert_eq!(
+            activity,
+            [
+                (Resource::Steps, date(1), "100"),
+                (Resource::Steps, date(2), "23"),
+                (Resource::VeryActive, date(1), "35"),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_directory() {
+        let dir = write_directory();
+        check(&import(&dir.0));
+    }
+
+    #[test]
+    fn invalid_file() {
+        let guard = TempPath(
+            std::env::temp_dir().join(format!("fitbit-rs-takeout-invalid-{}", std::process::id())),
+        );
+        let dir = &guard.0;
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("weight-2020-01-01.json"), "[{}]").unwrap();
+        fs::write(dir.join("steps-2020-01-01.json"), "[]").unwrap();
+        // Not an export file, and byte `len - 10` isn't a char boundary.
+        fs::write(dir.join("ü123456789.json"), "[]").unwrap();
+
+        let records: Vec<_> = Archive::open(dir).unwrap().records().collect();
+        assert_eq!(records.len(), 1);
+        assert!(
+            matches!(&records[0], Err(Error::Import { path, .. }) if path.ends_with("weight-2020-01-01.json"))
+        );
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(
+            Kind::from_file_name("weight-2020-01-01.json"),
+            Some(Kind::Weight)
+        );
+        assert_eq!(Kind::from_file_name("ü123456789.json"), None);
+        assert_eq!(Kind::from_file_name("sleep-2020-01-0ü.json"), None);
+        assert_eq!(Kind::from_file_name("2020-01-01.json"), None);
+        assert_eq!(Kind::from_file_name("Profile.json"), None);
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn import_zip() {
+        use std::io::Write;
+
+        let file = TempPath(
+            std::env::temp_dir().join(format!("fitbit-rs-takeout-{}.zip", std::process::id())),
+        );
+        let path = &file.0;
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, contents) in FILES {
+            zip.start_file(
+                format!("Takeout/Fitbit/{name}"),
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        check(&import(path));
+    }
+}
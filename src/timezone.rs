@@ -0,0 +1,111 @@
+//! Attach the user's timezone to the local timestamps Fitbit returns.
+//!
+//! Fitbit reports dates and times in the user's local time, without an
+//! offset. That's fine for a user who stays put, but once they travel their
+//! records stop lining up with each other and with anything else. A
+//! [`UserTimezone`] built from the user's
+//! [profile](crate::models::user::profile::User) turns those local timestamps
+//! into [`DateTime<FixedOffset>`] values.
+//!
+//! With the `chrono-tz` feature, the profile's IANA timezone is used so
+//! historic records get the offset which was in effect at the time, e.g.
+//! before a daylight saving change. Otherwise the profile's current offset is
+//! used for every timestamp.
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+
+use crate::models::user::profile::User;
+
+/// The timezone a user's records are logged in.
+#[derive(Debug, Clone, Copy)]
+pub struct UserTimezone {
+    offset: FixedOffset,
+    #[cfg(feature = "chrono-tz")]
+    tz: Option<chrono_tz::Tz>,
+}
+
+impl UserTimezone {
+    /// Use the same fixed offset for every timestamp.
+    pub fn from_offset(offset: FixedOffset) -> Self {
+        Self {
+            offset,
+            #[cfg(feature = "chrono-tz")]
+            tz: None,
+        }
+    }
+
+    /// Use the profile's timezone, falling back to its current
+    /// [`offset_from_utc`](User::offset_from_utc).
+    pub fn from_profile(user: &User) -> Self {
+        let offset = i32::try_from(user.offset_from_utc / 1000)
+            .ok()
+            .and_then(FixedOffset::east_opt)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+
+        Self {
+            offset,
+            #[cfg(feature = "chrono-tz")]
+            tz: user.timezone.parse().ok(),
+        }
+    }
+
+    /// Interpret `local` as a time in this timezone.
+    ///
+    /// Times which are ambiguous because of a daylight saving change resolve
+    /// to the earlier instant. Times which don't exist fall back to the fixed
+    /// offset.
+    pub fn localize(&self, local: NaiveDateTime) -> DateTime<FixedOffset> {
+        #[cfg(feature = "chrono-tz")]
+        if let Some(dt) = self
+            .tz
+            .and_then(|tz| tz.from_local_datetime(&local).earliest())
+        {
+            return dt.fixed_offset();
+        }
+
+        self.offset
+            .from_local_datetime(&local)
+            .single()
+            .expect("fixed offsets are never ambiguous")
+    }
+
+    /// The local time in this timezone at the UTC time `utc`.
+    pub fn local_time(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        #[cfg(feature = "chrono-tz")]
+        if let Some(tz) = self.tz {
+            return tz.from_utc_datetime(&utc).naive_local();
+        }
+
+        self.offset.from_utc_datetime(&utc).naive_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_offset() {
+        let tz = UserTimezone::from_offset(FixedOffset::east_opt(-7 * 3600).unwrap());
+        let local: NaiveDateTime = "2015-03-05T23:59:59".parse().unwrap();
+        assert_eq!(tz.localize(local).to_rfc3339(), "2015-03-05T23:59:59-07:00");
+
+        let utc: NaiveDateTime = "2015-03-06T06:59:59".parse().unwrap();
+        assert_eq!(tz.local_time(utc), local);
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn named_timezone() {
+        // Profile fetched in summer, record logged in winter.
+        let tz = UserTimezone {
+            offset: FixedOffset::east_opt(2 * 3600).unwrap(),
+            tz: Some(chrono_tz::Europe::Berlin),
+        };
+        let local: NaiveDateTime = "2015-01-05T08:00:00".parse().unwrap();
+        assert_eq!(tz.localize(local).to_rfc3339(), "2015-01-05T08:00:00+01:00");
+
+        let utc: NaiveDateTime = "2015-01-05T07:00:00".parse().unwrap();
+        assert_eq!(tz.local_time(utc), local);
+    }
+}
@@ -0,0 +1,180 @@
+//! Unit systems and typed physical quantities.
+//!
+//! Fitbit returns measurements as bare numbers whose unit depends on the
+//! `Accept-Language` header of the request. The [`Client`](crate::Client)
+//! pins a [`UnitSystem`] and sends the matching header on every request, so
+//! the numbers in a response can be turned into [`Mass`] and [`Length`]
+//! values which convert to any unit.
+
+const KILOGRAMS_PER_POUND: f64 = 0.453_592_37;
+const KILOGRAMS_PER_STONE: f64 = 14.0 * KILOGRAMS_PER_POUND;
+const METERS_PER_INCH: f64 = 0.0254;
+const METERS_PER_MILE: f64 = 1_609.344;
+
+/// The unit system measurements are sent and received in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Kilograms, kilometers and centimeters.
+    #[default]
+    Metric,
+    /// `en_US`: pounds, miles and inches.
+    Us,
+    /// `en_GB`: stone, kilometers and centimeters.
+    Uk,
+}
+
+impl UnitSystem {
+    /// Value of the `Accept-Language` header selecting this unit system.
+    /// Fitbit uses metric units when the header is missing.
+    pub(crate) fn accept_language(self) -> Option<&'static str> {
+        match self {
+            UnitSystem::Metric => None,
+            UnitSystem::Us => Some("en_US"),
+            UnitSystem::Uk => Some("en_GB"),
+        }
+    }
+
+    /// A body weight value as sent in this unit system.
+    pub fn weight(self, value: f32) -> Mass {
+        let value = f64::from(value);
+        match self {
+            UnitSystem::Metric => Mass::from_kilograms(value),
+            UnitSystem::Us => Mass::from_pounds(value),
+            UnitSystem::Uk => Mass::from_stones(value),
+        }
+    }
+
+    /// `mass` as a body weight value to send in this unit system.
+    pub fn weight_value(self, mass: Mass) -> f32 {
+        let value = match self {
+            UnitSystem::Metric => mass.kilograms(),
+            UnitSystem::Us => mass.pounds(),
+            UnitSystem::Uk => mass.stones(),
+        };
+        value as f32
+    }
+
+    /// A distance value as sent in this unit system.
+    pub fn distance(self, value: f32) -> Length {
+        let value = f64::from(value);
+        match self {
+            UnitSystem::Metric | UnitSystem::Uk => Length::from_kilometers(value),
+            UnitSystem::Us => Length::from_miles(value),
+        }
+    }
+
+    /// A height or body measurement value as sent in this unit system.
+    pub fn height(self, value: f32) -> Length {
+        let value = f64::from(value);
+        match self {
+            UnitSystem::Metric | UnitSystem::Uk => Length::from_centimeters(value),
+            UnitSystem::Us => Length::from_inches(value),
+        }
+    }
+}
+
+/// A mass, e.g. body weight.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Mass {
+    kilograms: f64,
+}
+
+impl Mass {
+    pub fn from_kilograms(kilograms: f64) -> Self {
+        Self { kilograms }
+    }
+
+    pub fn from_pounds(pounds: f64) -> Self {
+        Self::from_kilograms(pounds * KILOGRAMS_PER_POUND)
+    }
+
+    pub fn from_stones(stones: f64) -> Self {
+        Self::from_kilograms(stones * KILOGRAMS_PER_STONE)
+    }
+
+    pub fn kilograms(&self) -> f64 {
+        self.kilograms
+    }
+
+    pub fn pounds(&self) -> f64 {
+        self.kilograms / KILOGRAMS_PER_POUND
+    }
+
+    pub fn stones(&self) -> f64 {
+        self.kilograms / KILOGRAMS_PER_STONE
+    }
+}
+
+/// A length, e.g. a distance or height.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Length {
+    meters: f64,
+}
+
+impl Length {
+    pub fn from_meters(meters: f64) -> Self {
+        Self { meters }
+    }
+
+    pub fn from_kilometers(kilometers: f64) -> Self {
+        Self::from_meters(kilometers * 1000.0)
+    }
+
+    pub fn from_centimeters(centimeters: f64) -> Self {
+        Self::from_meters(centimeters / 100.0)
+    }
+
+    pub fn from_miles(miles: f64) -> Self {
+        Self::from_meters(miles * METERS_PER_MILE)
+    }
+
+    pub fn from_inches(inches: f64) -> Self {
+        Self::from_meters(inches * METERS_PER_INCH)
+    }
+
+    pub fn meters(&self) -> f64 {
+        self.meters
+    }
+
+    pub fn kilometers(&self) -> f64 {
+        self.meters / 1000.0
+    }
+
+    pub fn centimeters(&self) -> f64 {
+        self.meters * 100.0
+    }
+
+    pub fn miles(&self) -> f64 {
+        self.meters / METERS_PER_MILE
+    }
+
+    pub fn inches(&self) -> f64 {
+        self.meters / METERS_PER_INCH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn mass() {
+        assert_close(Mass::from_stones(1.0).pounds(), 14.0);
+        assert_close(UnitSystem::Us.weight(160.0).kilograms(), 72.574_779_2);
+        assert_close(UnitSystem::Uk.weight(11.5).kilograms(), 73.028_371_57);
+        assert_close(UnitSystem::Metric.weight(73.0).kilograms(), 73.0);
+        assert_eq!(UnitSystem::Us.weight_value(Mass::from_pounds(160.0)), 160.0);
+    }
+
+    #[test]
+    fn length() {
+        assert_close(UnitSystem::Us.distance(1.0).kilometers(), 1.609_344);
+        assert_close(UnitSystem::Uk.distance(1.0).meters(), 1000.0);
+        assert_close(UnitSystem::Us.height(70.0).centimeters(), 177.8);
+        assert_close(Length::from_meters(0.0254).inches(), 1.0);
+    }
+}
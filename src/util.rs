@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+#[cfg(test)]
+use std::path::PathBuf;
 
 use chrono::NaiveDate;
 
@@ -7,3 +9,30 @@ pub fn date_or_today<'a>(date: Option<NaiveDate>) -> Cow<'a, str> {
         Cow::Owned(d.format("%Y-%m-%d").to_string())
     })
 }
+
+/// Assert that `value` survives being serialized and deserialized again.
+#[cfg(test)]
+pub(crate) fn assert_round_trip<T>(value: &T)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_string(value).unwrap();
+    let back: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&back, value);
+}
+
+/// Removes the file or directory at its path when dropped, even if the test
+/// failed.
+#[cfg(test)]
+pub(crate) struct TempPath(pub(crate) PathBuf);
+
+#[cfg(test)]
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if self.0.is_dir() {
+            let _ = std::fs::remove_dir_all(&self.0);
+        } else {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+}
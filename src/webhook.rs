@@ -0,0 +1,100 @@
+//! Receive [subscription](crate::models::subscriptions) notifications.
+//!
+//! This module doesn't depend on any particular web framework. Wire the
+//! subscriber endpoint up to a [`Webhook`] as follows:
+//!
+//! - `GET` requests carry a `verify` query parameter. Respond with the status
+//!   code returned by [`Webhook::verify`].
+//! - `POST` requests carry the notifications. Pass the raw body and the
+//!   [`SIGNATURE_HEADER`] value to [`Webhook::parse`], then respond with `204
+//!   No Content` within a few seconds. Fetch the changed data afterwards.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::{error::Error, models::subscriptions::Notification, Result};
+
+/// Header containing the base64 encoded HMAC-SHA1 signature of the body.
+pub const SIGNATURE_HEADER: &str = "X-Fitbit-Signature";
+
+/// Verifies and parses requests sent to a subscriber endpoint.
+pub struct Webhook {
+    client_secret: String,
+    verification_code: String,
+}
+
+impl Webhook {
+    /// `verification_code` is the code shown in the application settings
+    /// after adding the subscriber endpoint.
+    pub fn new(client_secret: &str, verification_code: &str) -> Self {
+        Self {
+            client_secret: client_secret.to_owned(),
+            verification_code: verification_code.to_owned(),
+        }
+    }
+
+    /// Answer a verification request, given the value of its `verify` query
+    /// parameter. Returns the HTTP status code to respond with: `204` if the
+    /// code matches, `404` otherwise.
+    pub fn verify(&self, code: &str) -> u16 {
+        if code == self.verification_code {
+            204
+        } else {
+            404
+        }
+    }
+
+    /// Check that `body` was signed by Fitbit using the client secret.
+    pub fn is_signature_valid(&self, body: &[u8], signature: &str) -> bool {
+        let Ok(signature) = STANDARD.decode(signature.trim()) else {
+            return false;
+        };
+
+        let key = format!("{}&", self.client_secret);
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(key.as_bytes()).expect("HMAC can take a key of any size");
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Verify the signature of a notification request and parse its body.
+    pub fn parse(&self, body: &[u8], signature: &str) -> Result<Vec<Notification>> {
+        if !self.is_signature_valid(body, signature) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::subscriptions::CollectionType;
+
+    const BODY: &[u8] = br#"[{"collectionType":"foods","date":"2020-06-01","ownerId":"184X36","ownerType":"user","subscriptionId":"2345"}]"#;
+
+    #[test]
+    fn verify() {
+        let webhook = Webhook::new("secret", "code");
+        assert_eq!(webhook.verify("code"), 204);
+        assert_eq!(webhook.verify("wrong"), 404);
+    }
+
+    #[test]
+    fn parse_signed() {
+        let webhook = Webhook::new("secret", "code");
+        let notifications = webhook.parse(BODY, "DDEQPco30FLDW1BGKjCVBDiFkgk=").unwrap();
+        assert_eq!(notifications[0].collection_type, CollectionType::Foods);
+    }
+
+    #[test]
+    fn reject_bad_signature() {
+        let webhook = Webhook::new("other secret", "code");
+        assert!(matches!(
+            webhook.parse(BODY, "DDEQPco30FLDW1BGKjCVBDiFkgk="),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(!webhook.is_signature_valid(BODY, "not base64!"));
+    }
+}
@@ -0,0 +1,468 @@
+//! Bulk import weight and body fat measurements, e.g. from another scale.
+//!
+//! Measurements are read from CSV with the columns `date`, `time`,
+//! `weight`, `fat` and `unit`:
+//!
+//! ```text
+//! date,time,weight,fat,unit
+//! 2024-01-01,07:12:00,165.3,21.5,lb
+//! 2024-01-02,07:10,75.1,,kg
+//! ```
+//!
+//! `time`, `weight`, `fat` and `unit` may be empty. Weights without a unit
+//! are in kilograms, and fat is a percentage.
+//!
+//! A [`WeightImporter`] skips measurements which are already logged: a
+//! weight or fat log within a tolerance of the same time, even if on
+//! another date, or on the same date for measurements without a time.
+//! Everything else is logged, waiting for the rate limit to reset whenever
+//! it runs out.
+
+use std::{fmt, io::Read};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{Deserialize, Deserializer};
+
+use crate::{error::Error, rate_limit::throttled, units::Mass, Client, DateRange, Result};
+
+/// Tolerance used unless configured otherwise.
+const DEFAULT_TOLERANCE: TimeDelta = TimeDelta::minutes(5);
+/// Largest tolerance allowed.
+const MAX_TOLERANCE: TimeDelta = TimeDelta::days(1);
+
+/// A weight and/or body fat measurement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub date: NaiveDate,
+    /// Measurements without a time match any log on the same date.
+    pub time: Option<NaiveTime>,
+    pub weight: Option<Mass>,
+    /// Body fat percentage.
+    pub fat: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum Unit {
+    #[serde(rename = "kg")]
+    Kilograms,
+    #[serde(rename = "lb", alias = "lbs")]
+    Pounds,
+    #[serde(rename = "st")]
+    Stones,
+}
+
+#[derive(Deserialize)]
+struct Row {
+    date: NaiveDate,
+    #[serde(default, deserialize_with = "time")]
+    time: Option<NaiveTime>,
+    weight: Option<f64>,
+    fat: Option<f32>,
+    unit: Option<Unit>,
+}
+
+/// Parse `HH:MM:SS` or `HH:MM`, or nothing.
+fn time<'de, D>(deserializer: D) -> std::result::Result<Option<NaiveTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    if s.is_empty() {
+        return Ok(None);
+    }
+    NaiveTime::parse_from_str(&s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(&s, "%H:%M"))
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Read measurements from CSV with a header row.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Measurement>> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .deserialize()
+        .map(|row| {
+            let row: Row = row?;
+            let weight = row
+                .weight
+                .map(|weight| match row.unit.unwrap_or(Unit::Kilograms) {
+                    Unit::Kilograms => Mass::from_kilograms(weight),
+                    Unit::Pounds => Mass::from_pounds(weight),
+                    Unit::Stones => Mass::from_stones(weight),
+                });
+            Ok(Measurement {
+                date: row.date,
+                time: row.time,
+                weight,
+                fat: row.fat,
+            })
+        })
+        .collect()
+}
+
+/// A log to create.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlannedLog {
+    /// Weight in the client's unit system, rounded to two decimals.
+    Weight {
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+        weight: f32,
+    },
+    Fat {
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+        fat: f32,
+    },
+}
+
+impl fmt::Display for PlannedLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, date, time, value) = match self {
+            PlannedLog::Weight { date, time, weight } => ("weight", date, time, weight),
+            PlannedLog::Fat { date, time, fat } => ("fat", date, time, fat),
+        };
+        write!(f, "{kind} {date}")?;
+        if let Some(time) = time {
+            write!(f, " {time}")?;
+        }
+        write!(f, ": {value}")
+    }
+}
+
+/// Outcome of an import.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Measurements read.
+    pub measurements: usize,
+    /// Weights and fat values skipped because they're already logged.
+    pub duplicates: usize,
+    /// Logs which were, or in a dry run would have been, created.
+    pub planned: Vec<PlannedLog>,
+    /// Logs which failed to be created, with the reason.
+    pub failed: Vec<(PlannedLog, Error)>,
+    pub dry_run: bool,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} measurements, {} values already logged",
+            self.measurements, self.duplicates
+        )?;
+        if self.dry_run {
+            writeln!(f, "Dry run, would create {} logs:", self.planned.len())?;
+            for log in &self.planned {
+                writeln!(f, "  {log}")?;
+            }
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "Created {} logs, {} failed",
+            self.planned.len() - self.failed.len(),
+            self.failed.len()
+        )?;
+        for (log, err) in &self.failed {
+            writeln!(f, "  {log}: {err}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the weight and fat logs which are missing from a user's data.
+pub struct WeightImporter<'client> {
+    client: &'client Client,
+    tolerance: TimeDelta,
+    dry_run: bool,
+    user_id: Option<String>,
+}
+
+impl<'client> WeightImporter<'client> {
+    pub fn new(client: &'client Client) -> Self {
+        Self {
+            client,
+            tolerance: DEFAULT_TOLERANCE,
+            dry_run: false,
+            user_id: None,
+        }
+    }
+
+    /// How far apart a measurement and an existing log may be for them to
+    /// count as the same. Defaults to five minutes. Negative tolerances are
+    /// taken as zero and ones longer than a day as a day.
+    pub fn with_tolerance(mut self, tolerance: TimeDelta) -> Self {
+        self.tolerance = tolerance.clamp(TimeDelta::zero(), MAX_TOLERANCE);
+        self
+    }
+
+    /// Only plan the logs to create, without creating them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Import into another user's data instead of the authenticated user's.
+    pub fn with_user_id(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_owned());
+        self
+    }
+
+    /// Create logs for the `measurements` which aren't logged yet.
+    ///
+    /// Failing to fetch the existing logs fails the import. Failing to
+    /// create a log is recorded in the report and the import carries on.
+    pub async fn run(&self, measurements: &[Measurement]) -> Result<Report> {
+        let mut report = Report {
+            measurements: measurements.len(),
+            dry_run: self.dry_run,
+            ..Report::default()
+        };
+        // Logs within the tolerance may be on the day before or after.
+        let bounds = measurements.iter().map(|m| match m.time {
+            Some(time) => {
+                let at = m.date.and_time(time);
+                let date = |at: Option<NaiveDateTime>| at.map_or(m.date, |at| at.date());
+                (
+                    date(at.checked_sub_signed(self.tolerance)),
+                    date(at.checked_add_signed(self.tolerance)),
+                )
+            }
+            None => (m.date, m.date),
+        });
+        let (Some(start), Some(end)) =
+            (bounds.clone().map(|b| b.0).min(), bounds.map(|b| b.1).max())
+        else {
+            return Ok(report);
+        };
+        let range = DateRange::new(start, end)?;
+        let client = self.client;
+        let user_id = self.user_id.as_deref();
+
+        let weights = throttled(client, || async move {
+            client
+                .body_time_series()
+                .get_weight_time_series_by_date_range(range, user_id)
+                .await
+        })
+        .await?;
+        let fats = throttled(client, || async move {
+            client
+                .body_time_series()
+                .get_body_fat_time_series_by_date_range(range, user_id)
+                .await
+        })
+        .await?;
+
+        let mut logged_weights: Vec<_> = weights.iter().map(|l| (l.date, Some(l.time))).collect();
+        let mut logged_fats: Vec<_> = fats.iter().map(|l| (l.date, Some(l.time))).collect();
+        let units = client.unit_system();
+        for m in measurements {
+            if let Some(weight) = m.weight {
+                if self.is_logged(&logged_weights, m) {
+                    report.duplicates += 1;
+                } else {
+                    logged_weights.push((m.date, m.time));
+                    report.planned.push(PlannedLog::Weight {
+                        date: m.date,
+                        time: m.time,
+                        weight: (units.weight_value(weight) * 100.0).round() / 100.0,
+                    });
+                }
+            }
+            if let Some(fat) = m.fat {
+                if self.is_logged(&logged_fats, m) {
+                    report.duplicates += 1;
+                } else {
+                    logged_fats.push((m.date, m.time));
+                    report.planned.push(PlannedLog::Fat {
+                        date: m.date,
+                        time: m.time,
+                        fat,
+                    });
+                }
+            }
+        }
+
+        if self.dry_run {
+            return Ok(report);
+        }
+        for log in &report.planned {
+            let result = match *log {
+                PlannedLog::Weight { date, time, weight } => {
+                    throttled(client, || async move {
+                        client
+                            .body()
+                            .log_weight(weight, date, time, user_id)
+                            .await
+                            .map(drop)
+                    })
+                    .await
+                }
+                PlannedLog::Fat { date, time, fat } => {
+                    throttled(client, || async move {
+                        client
+                            .body()
+                            .log_body_fat(fat, date, time, user_id)
+                            .await
+                            .map(drop)
+                    })
+                    .await
+                }
+            };
+            if let Err(err) = result {
+                report.failed.push((log.clone(), err));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Whether `logged` has a log close enough to the time of `measurement`,
+    /// even if that's across midnight. Missing times match any time on the
+    /// same date.
+    fn is_logged(
+        &self,
+        logged: &[(NaiveDate, Option<NaiveTime>)],
+        measurement: &Measurement,
+    ) -> bool {
+        logged
+            .iter()
+            .any(|&(date, time)| match (time, measurement.time) {
+                (Some(a), Some(b)) => {
+                    (date.and_time(a) - measurement.date.and_time(b)).abs() <= self.tolerance
+                }
+                _ => date == measurement.date,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::FutureExt;
+
+    use super::*;
+    use crate::middleware::{LocalBoxFuture, Middleware, Next};
+
+    const CSV: &str = "\
+date,time,weight,fat,unit
+2024-01-01,07:12,165.3,21.5,lb
+2024-01-01,19:00:00,75.3,,kg
+2024-01-02,,11.8,,st
+2024-01-02,08:00:00,,20.9,
+";
+
+    /// Serves existing logs and records the requests creating new ones.
+    #[derive(Clone, Default)]
+    struct Fitbit(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware for Fitbit {
+        fn handle<'a>(
+            &'a self,
+            request: reqwest::Request,
+            _next: Next<'a>,
+        ) -> LocalBoxFuture<'a, Result<reqwest::Response>> {
+            let path = request.url().path().to_owned();
+            let body = match (request.method().as_str(), path.as_str()) {
+                ("GET", "/1/user/-/body/log/weight/date/2024-01-01/2024-01-02.json") => {
+                    r#"{"weight":[{"bmi":23.57,"date":"2024-01-01","logId":1,"time":"07:15:00","weight":75.0}]}"#
+                }
+                ("GET", "/1/user/-/body/log/fat/date/2024-01-01/2024-01-02.json") => {
+                    r#"{"fat":[]}"#
+                }
+                ("POST", "/1/user/-/body/log/weight.json") => {
+                    r#"{"weightLog":{"bmi":23.57,"date":"2024-01-01","logId":2,"time":"07:15:00","weight":75.0}}"#
+                }
+                ("POST", "/1/user/-/body/log/fat.json") => {
+                    r#"{"fatLog":{"date":"2024-01-01","fat":21.5,"logId":3,"time":"07:15:00","source":"API"}}"#
+                }
+                _ => panic!("unexpected request to {path}"),
+            };
+            if request.method() == "POST" {
+                let form = request.body().and_then(|b| b.as_bytes()).unwrap();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{path}?{}", String::from_utf8_lossy(form)));
+            }
+            let response = http::Response::new(body);
+            async move { Ok(response.into()) }.boxed_local()
+        }
+    }
+
+    #[test]
+    fn read() {
+        let measurements = read_csv(CSV.as_bytes()).unwrap();
+        assert_eq!(measurements.len(), 4);
+        assert!((measurements[0].weight.unwrap().kilograms() - 74.979).abs() < 0.001);
+        assert_eq!(measurements[0].time, NaiveTime::from_hms_opt(7, 12, 0));
+        assert_eq!(measurements[2].time, None);
+        assert!((measurements[2].weight.unwrap().pounds() - 165.2).abs() < 0.001);
+        assert_eq!(measurements[3].weight, None);
+
+        assert!(read_csv("date,time,weight,fat,unit\n2024-01-01,,70,,oz\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn across_midnight() {
+        let client = Client::new("id", "secret");
+        let importer = WeightImporter::new(&client);
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let time = |s: &str| Some(s.parse::<NaiveTime>().unwrap());
+        let measurement = Measurement {
+            date: date("2024-01-01"),
+            time: time("23:58:00"),
+            weight: None,
+            fat: None,
+        };
+
+        assert!(importer.is_logged(&[(date("2024-01-02"), time("00:01:00"))], &measurement));
+        assert!(!importer.is_logged(&[(date("2024-01-02"), time("00:10:00"))], &measurement));
+        assert!(!importer.is_logged(&[(date("2024-01-02"), None)], &measurement));
+        assert!(importer.is_logged(&[(date("2024-01-01"), None)], &measurement));
+
+        let importer = WeightImporter::new(&client).with_tolerance(TimeDelta::minutes(-10));
+        assert_eq!(importer.tolerance, TimeDelta::zero());
+        let importer = WeightImporter::new(&client).with_tolerance(TimeDelta::MAX);
+        assert_eq!(importer.tolerance, TimeDelta::days(1));
+    }
+
+    #[tokio::test]
+    async fn import() {
+        let fitbit = Fitbit::default();
+        let client = Client::new("id", "secret").with_middleware(fitbit.clone());
+        let measurements = read_csv(CSV.as_bytes()).unwrap();
+
+        let report = WeightImporter::new(&client)
+            .with_dry_run(true)
+            .run(&measurements)
+            .await
+            .unwrap();
+        // The first weight is within five minutes of the logged one.
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.planned.len(), 4);
+        assert!(fitbit.0.lock().unwrap().is_empty());
+        assert!(report.to_string().contains("would create 4 logs"));
+
+        let report = WeightImporter::new(&client)
+            .with_tolerance(TimeDelta::minutes(1))
+            .run(&measurements)
+            .await
+            .unwrap();
+        assert_eq!(report.duplicates, 0);
+        assert!(report.failed.is_empty());
+        assert_eq!(
+            *fitbit.0.lock().unwrap(),
+            [
+                "/1/user/-/body/log/weight.json?weight=74.98&date=2024-01-01&time=07%3A12%3A00",
+                "/1/user/-/body/log/fat.json?fat=21.5&date=2024-01-01&time=07%3A12%3A00",
+                "/1/user/-/body/log/weight.json?weight=75.3&date=2024-01-01&time=19%3A00%3A00",
+                "/1/user/-/body/log/weight.json?weight=74.93&date=2024-01-02",
+                "/1/user/-/body/log/fat.json?fat=20.9&date=2024-01-02&time=08%3A00%3A00",
+            ]
+        );
+    }
+}